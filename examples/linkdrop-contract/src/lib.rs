@@ -0,0 +1,170 @@
+//! Port of the `linkdrop` contract of `near/core-contracts` to near-sdk 5, deployed to the
+//! `near` top level account of a sandbox built with `with_standard_contracts()`.
+//!
+//! It creates `*.near` accounts with `create_account`, and drops NEAR to a key that can later
+//! claim it into an existing account or a new one.
+
+use near_sdk::serde_json::{self, json};
+use near_sdk::store::LookupMap;
+use near_sdk::{AccountId, Gas, NearToken, PanicOnDefault, Promise, PublicKey, env, near, require};
+
+/// Gas attached to the callback checking whether an account was created.
+const ON_CREATE_ACCOUNT_GAS: Gas = Gas::from_tgas(13);
+
+/// Allowance of the keys added by `send` to claim the NEAR dropped to them.
+const ACCESS_KEY_ALLOWANCE: NearToken = NearToken::from_near(1);
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct LinkDrop {
+    accounts: LookupMap<PublicKey, NearToken>,
+}
+
+#[near]
+impl LinkDrop {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            accounts: LookupMap::new(b"a"),
+        }
+    }
+
+    /// Create the account `new_account_id` with `new_public_key` as its full access key,
+    /// funded with the attached deposit. The deposit is refunded if the account could not be
+    /// created, such as when it already exists.
+    #[payable]
+    pub fn create_account(
+        &mut self,
+        new_account_id: AccountId,
+        new_public_key: PublicKey,
+    ) -> Promise {
+        let amount = env::attached_deposit();
+        Promise::new(new_account_id)
+            .create_account()
+            .add_full_access_key(new_public_key)
+            .transfer(amount)
+            .then(callback(
+                "on_account_created",
+                json!({
+                    "predecessor_account_id": env::predecessor_account_id(),
+                    "amount": amount,
+                }),
+            ))
+    }
+
+    /// Drop the attached deposit to `public_key`, which is added as an access key allowed to
+    /// claim it with `claim` or `create_account_and_claim`.
+    #[payable]
+    pub fn send(&mut self, public_key: PublicKey) -> Promise {
+        let amount = env::attached_deposit();
+        require!(
+            amount > ACCESS_KEY_ALLOWANCE,
+            "Attached deposit must be greater than the access key allowance"
+        );
+        let balance = self.accounts.get(&public_key).copied().unwrap_or_default();
+        self.accounts.insert(
+            public_key.clone(),
+            balance
+                .saturating_add(amount)
+                .saturating_sub(ACCESS_KEY_ALLOWANCE),
+        );
+        Promise::new(env::current_account_id()).add_access_key_allowance(
+            public_key,
+            near_sdk::Allowance::limited(ACCESS_KEY_ALLOWANCE).unwrap(),
+            env::current_account_id(),
+            "claim,create_account_and_claim",
+        )
+    }
+
+    /// Claim the NEAR dropped to the signing key into the existing `account_id`.
+    #[private]
+    pub fn claim(&mut self, account_id: AccountId) -> Promise {
+        let amount = self.take_drop();
+        Promise::new(env::current_account_id())
+            .delete_key(env::signer_account_pk())
+            .detach();
+        Promise::new(account_id).transfer(amount)
+    }
+
+    /// Claim the NEAR dropped to the signing key into the new account `new_account_id`.
+    #[private]
+    pub fn create_account_and_claim(
+        &mut self,
+        new_account_id: AccountId,
+        new_public_key: PublicKey,
+    ) -> Promise {
+        let amount = self.take_drop();
+        Promise::new(new_account_id)
+            .create_account()
+            .add_full_access_key(new_public_key)
+            .transfer(amount)
+            .then(callback(
+                "on_account_created_and_claimed",
+                json!({ "amount": amount }),
+            ))
+    }
+
+    /// Refund the deposit of `create_account` if the account could not be created.
+    #[private]
+    pub fn on_account_created(
+        &mut self,
+        predecessor_account_id: AccountId,
+        amount: NearToken,
+    ) -> bool {
+        let created = is_promise_success();
+        if !created {
+            Promise::new(predecessor_account_id)
+                .transfer(amount)
+                .detach();
+        }
+        created
+    }
+
+    /// Restore the drop if the account of `create_account_and_claim` could not be created,
+    /// and remove the signing key otherwise.
+    #[private]
+    pub fn on_account_created_and_claimed(&mut self, amount: NearToken) -> bool {
+        let created = is_promise_success();
+        if created {
+            Promise::new(env::current_account_id())
+                .delete_key(env::signer_account_pk())
+                .detach();
+        } else {
+            self.accounts.insert(env::signer_account_pk(), amount);
+        }
+        created
+    }
+
+    /// The NEAR dropped to `key` and not claimed yet.
+    pub fn get_key_balance(&self, key: PublicKey) -> NearToken {
+        self.accounts
+            .get(&key)
+            .copied()
+            .unwrap_or_else(|| env::panic_str("Key is missing"))
+    }
+}
+
+impl LinkDrop {
+    fn take_drop(&mut self) -> NearToken {
+        self.accounts
+            .remove(&env::signer_account_pk())
+            .unwrap_or_else(|| env::panic_str("Unexpected public key"))
+    }
+}
+
+fn callback(method: &str, args: serde_json::Value) -> Promise {
+    Promise::new(env::current_account_id()).function_call(
+        method,
+        serde_json::to_vec(&args).unwrap(),
+        NearToken::from_yoctonear(0),
+        ON_CREATE_ACCOUNT_GAS,
+    )
+}
+
+fn is_promise_success() -> bool {
+    require!(
+        env::promise_results_count() == 1,
+        "Expected one promise result"
+    );
+    env::promise_result_checked(0, 0).is_ok()
+}
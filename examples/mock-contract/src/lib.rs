@@ -0,0 +1,119 @@
+//! Contract behind the `MockContract` of near-workspaces, bundled in `workspaces/res/mock.wasm`.
+//!
+//! It exports no mocked method by itself: near-workspaces appends an export for each one,
+//! calling `mock_handle` with the index of the method. The response of each method is
+//! configured in state through `mock_configure`, so that reprogramming a method needs no
+//! redeploy.
+
+use near_sdk::{AccountId, Gas, NearToken, env, require};
+
+/// Storage key of the number of calls recorded so far, as a little endian `u64`.
+const COUNT_KEY: &[u8] = b"n";
+
+/// Prefix of the storage keys of recorded calls, each followed by the little endian `u64`
+/// index of the call and then the name of the method called.
+const CALL_PREFIX: &[u8] = b"c";
+
+/// Prefix of the storage keys of the configuration of each method, followed by its little
+/// endian `u32` index.
+const METHOD_PREFIX: &[u8] = b"m";
+
+// Kinds of response of a configured method.
+const RETURN_VALUE: u8 = 0;
+const PANIC: u8 = 1;
+const RETURN_PROMISE: u8 = 2;
+
+/// Configure the method at an index, with the input laid out as the little endian `u32`
+/// index followed by its configuration: the length of the method name (`u32`), the name, the
+/// kind of response (`u8`) and then the response.
+///
+/// A value to return or a panic message take the rest of the configuration. A promise is
+/// laid out as the length of the receiver id (`u32`) and the receiver id, the length of the
+/// function name (`u32`) and the function name, the deposit (`u128`), the gas (`u64`) and then
+/// the arguments. Only the mock account itself may configure its methods.
+#[unsafe(no_mangle)]
+pub extern "C" fn mock_configure() {
+    require!(
+        env::predecessor_account_id() == env::current_account_id(),
+        "Only the mock account can configure its methods"
+    );
+    let input = env::input().unwrap_or_default();
+    let Some((index, config)) = input.split_at_checked(4) else {
+        env::panic_str("Invalid mock configuration");
+    };
+    env::storage_write(&[METHOD_PREFIX, index].concat(), config);
+}
+
+/// Record the call to the method at `index`, then respond as configured. It is called by the
+/// export of each mocked method, and is not exported itself once the mock is deployed.
+#[unsafe(no_mangle)]
+pub extern "C" fn mock_handle(index: u32) {
+    let config = env::storage_read(&[METHOD_PREFIX, &index.to_le_bytes()].concat())
+        .unwrap_or_else(|| env::panic_str("Mocked method is not configured"));
+    let mut config = Reader(&config);
+    record_call(config.bytes());
+
+    match config.take(1)[0] {
+        RETURN_VALUE => env::value_return(config.0),
+        PANIC => env::panic_str(&String::from_utf8_lossy(config.0)),
+        RETURN_PROMISE => {
+            let receiver_id: AccountId = String::from_utf8_lossy(config.bytes())
+                .parse()
+                .unwrap_or_else(|_| env::panic_str("Invalid mock promise receiver"));
+            let function_name = String::from_utf8_lossy(config.bytes()).into_owned();
+            let deposit = u128::from_le_bytes(config.take(16).try_into().unwrap());
+            let gas = u64::from_le_bytes(config.take(8).try_into().unwrap());
+            let promise = env::promise_create(
+                receiver_id,
+                &function_name,
+                config.0,
+                NearToken::from_yoctonear(deposit),
+                Gas::from_gas(gas),
+            );
+            env::promise_return(promise);
+        }
+        _ => env::panic_str("Invalid mock response"),
+    }
+}
+
+/// Record the call to `method` under [`CALL_PREFIX`] ++ index ++ method name, with the value
+/// laid out as the deposit (`u128`), the gas (`u64`), the length of the predecessor id
+/// (`u32`) and the predecessor id, followed by the arguments.
+fn record_call(method: &[u8]) {
+    let count = env::storage_read(COUNT_KEY)
+        .map(|count| u64::from_le_bytes(count.try_into().unwrap()))
+        .unwrap_or(0);
+    let predecessor_id = env::predecessor_account_id();
+    let record = [
+        &env::attached_deposit().as_yoctonear().to_le_bytes()[..],
+        &env::prepaid_gas().as_gas().to_le_bytes(),
+        &(predecessor_id.len() as u32).to_le_bytes(),
+        predecessor_id.as_bytes(),
+        &env::input().unwrap_or_default(),
+    ]
+    .concat();
+    env::storage_write(
+        &[CALL_PREFIX, &count.to_le_bytes(), method].concat(),
+        &record,
+    );
+    env::storage_write(COUNT_KEY, &(count + 1).to_le_bytes());
+}
+
+/// Reads the configuration of a method from its start.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let Some((taken, rest)) = self.0.split_at_checked(len) else {
+            env::panic_str("Invalid mock configuration");
+        };
+        self.0 = rest;
+        taken
+    }
+
+    /// Bytes prefixed by their little endian `u32` length.
+    fn bytes(&mut self) -> &'a [u8] {
+        let len = u32::from_le_bytes(self.take(4).try_into().unwrap());
+        self.take(len as usize)
+    }
+}
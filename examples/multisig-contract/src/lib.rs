@@ -0,0 +1,425 @@
+//! Port of the `multisig2` contract of `near/core-contracts` to near-sdk 5, deployed by the
+//! multisig factory of a sandbox built with `with_standard_contracts()`.
+//!
+//! Members, either accounts or access keys of the multisig account itself, add requests of
+//! actions to run from the multisig account. A request runs once `num_confirmations` members
+//! confirmed it.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use near_sdk::json_types::{Base64VecU8, U64, U128};
+use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+use near_sdk::{
+    AccountId, Allowance, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue, PublicKey, env,
+    near, require,
+};
+
+pub type RequestId = u32;
+
+/// Methods the access keys of members are allowed to call.
+const MULTISIG_METHOD_NAMES: &str = "add_request,delete_request,confirm,add_request_and_confirm";
+
+/// Default number of requests a member may have pending.
+const DEFAULT_ACTIVE_REQUESTS_LIMIT: u32 = 12;
+
+/// Time after which the member who added a request may delete it, in nanoseconds.
+const REQUEST_COOLDOWN: u64 = 900_000_000_000;
+
+/// Allowance of the access keys of members, which pay for their own calls.
+const MEMBER_KEY_ALLOWANCE: NearToken = NearToken::from_near(10);
+
+/// A member allowed to add and confirm requests.
+#[near(serializers = [borsh, json])]
+#[serde(untagged)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MultisigMember {
+    AccessKey { public_key: PublicKey },
+    Account { account_id: AccountId },
+}
+
+impl fmt::Display for MultisigMember {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AccessKey { public_key } => {
+                write!(f, "{}", String::from(public_key))
+            }
+            Self::Account { account_id } => write!(f, "{account_id}"),
+        }
+    }
+}
+
+/// Permission of a function call access key added by a request.
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct FunctionCallPermission {
+    allowance: Option<U128>,
+    receiver_id: AccountId,
+    method_names: Vec<String>,
+}
+
+/// An action run from the multisig account once its request is confirmed.
+#[near(serializers = [borsh, json])]
+#[serde(tag = "type")]
+#[derive(Clone, Debug)]
+pub enum MultiSigRequestAction {
+    Transfer {
+        amount: U128,
+    },
+    CreateAccount,
+    DeployContract {
+        code: Base64VecU8,
+    },
+    AddMember {
+        member: MultisigMember,
+    },
+    DeleteMember {
+        member: MultisigMember,
+    },
+    AddKey {
+        public_key: PublicKey,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        permission: Option<FunctionCallPermission>,
+    },
+    DeleteKey {
+        public_key: PublicKey,
+    },
+    FunctionCall {
+        method_name: String,
+        args: Base64VecU8,
+        deposit: U128,
+        gas: U64,
+    },
+    SetNumConfirmations {
+        num_confirmations: u32,
+    },
+    SetActiveRequestsLimit {
+        active_requests_limit: u32,
+    },
+}
+
+/// Actions to run from the multisig account against `receiver_id`.
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct MultiSigRequest {
+    receiver_id: AccountId,
+    actions: Vec<MultiSigRequestAction>,
+}
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct MultiSigRequestWithSigner {
+    request: MultiSigRequest,
+    member: MultisigMember,
+    added_timestamp: u64,
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct MultiSigContract {
+    members: IterableSet<MultisigMember>,
+    num_confirmations: u32,
+    request_nonce: RequestId,
+    requests: IterableMap<RequestId, MultiSigRequestWithSigner>,
+    confirmations: LookupMap<RequestId, HashSet<String>>,
+    num_requests_pk: LookupMap<String, u32>,
+    active_requests_limit: u32,
+}
+
+#[near]
+impl MultiSigContract {
+    /// Initialize with the given `members`, `num_confirmations` of which must confirm each
+    /// request. The members using an access key get a key allowed to call this contract.
+    #[init]
+    pub fn new(members: Vec<MultisigMember>, num_confirmations: u32) -> Self {
+        require!(
+            members.len() >= num_confirmations as usize,
+            "Members list must be equal or larger than number of confirmations"
+        );
+        let mut this = Self {
+            members: IterableSet::new(b"m"),
+            num_confirmations,
+            request_nonce: 0,
+            requests: IterableMap::new(b"r"),
+            confirmations: LookupMap::new(b"c"),
+            num_requests_pk: LookupMap::new(b"k"),
+            active_requests_limit: DEFAULT_ACTIVE_REQUESTS_LIMIT,
+        };
+        let mut promise = None;
+        for member in members {
+            promise = this.add_member(promise, member);
+        }
+        if let Some(promise) = promise {
+            promise.detach();
+        }
+        this
+    }
+
+    /// Add `request` and return its ID.
+    pub fn add_request(&mut self, request: MultiSigRequest) -> RequestId {
+        let member = self.current_member();
+        let num_requests = self
+            .num_requests_pk
+            .get(&member.to_string())
+            .copied()
+            .unwrap_or(0)
+            + 1;
+        require!(
+            num_requests <= self.active_requests_limit,
+            "Account has too many active requests. Confirm or delete some."
+        );
+        self.num_requests_pk
+            .insert(member.to_string(), num_requests);
+
+        let request_id = self.request_nonce;
+        self.requests.insert(
+            request_id,
+            MultiSigRequestWithSigner {
+                request,
+                member,
+                added_timestamp: env::block_timestamp(),
+            },
+        );
+        self.confirmations.insert(request_id, HashSet::new());
+        self.request_nonce += 1;
+        request_id
+    }
+
+    /// Add `request` and confirm it as the member adding it.
+    pub fn add_request_and_confirm(&mut self, request: MultiSigRequest) -> PromiseOrValue<bool> {
+        let request_id = self.add_request(request);
+        self.confirm(request_id)
+    }
+
+    /// Delete the request `request_id`, which only the member who added it may do once the
+    /// cooldown passed.
+    pub fn delete_request(&mut self, request_id: RequestId) -> MultiSigRequest {
+        let request = self.request(request_id);
+        require!(
+            request.member == self.current_member(),
+            "Only the member who added the request may delete it"
+        );
+        require!(
+            env::block_timestamp() > request.added_timestamp + REQUEST_COOLDOWN,
+            "Request cannot be deleted before the cooldown"
+        );
+        self.remove_request(request_id).request
+    }
+
+    /// Confirm the request `request_id`, running it once it has enough confirmations.
+    pub fn confirm(&mut self, request_id: RequestId) -> PromiseOrValue<bool> {
+        let member = self.current_member();
+        let mut confirmations = self
+            .confirmations
+            .get(&request_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("No such request"));
+        require!(
+            confirmations.insert(member.to_string()),
+            "Already confirmed this request with this key"
+        );
+        if confirmations.len() < self.num_confirmations as usize {
+            self.confirmations.insert(request_id, confirmations);
+            return PromiseOrValue::Value(true);
+        }
+
+        let request = self.remove_request(request_id).request;
+        self.execute_request(request)
+    }
+
+    pub fn get_request(&self, request_id: RequestId) -> MultiSigRequest {
+        self.request(request_id).request
+    }
+
+    pub fn get_num_requests_pk(&self, public_key: PublicKey) -> u32 {
+        let member = MultisigMember::AccessKey { public_key };
+        self.num_requests_pk
+            .get(&member.to_string())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn list_request_ids(&self) -> Vec<RequestId> {
+        self.requests.keys().copied().collect()
+    }
+
+    pub fn get_confirmations(&self, request_id: RequestId) -> Vec<String> {
+        self.confirmations
+            .get(&request_id)
+            .unwrap_or_else(|| env::panic_str("No such request"))
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_num_confirmations(&self) -> u32 {
+        self.num_confirmations
+    }
+
+    pub fn get_request_nonce(&self) -> u32 {
+        self.request_nonce
+    }
+
+    pub fn get_members(&self) -> Vec<MultisigMember> {
+        self.members.iter().cloned().collect()
+    }
+}
+
+impl MultiSigContract {
+    /// The member calling: the signing key for calls from the multisig account itself, and
+    /// the predecessor account otherwise.
+    fn current_member(&self) -> MultisigMember {
+        let member = if env::current_account_id() == env::predecessor_account_id() {
+            MultisigMember::AccessKey {
+                public_key: env::signer_account_pk(),
+            }
+        } else {
+            MultisigMember::Account {
+                account_id: env::predecessor_account_id(),
+            }
+        };
+        require!(self.members.contains(&member), "Caller is not a member");
+        member
+    }
+
+    fn request(&self, request_id: RequestId) -> MultiSigRequestWithSigner {
+        self.requests
+            .get(&request_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("No such request"))
+    }
+
+    fn remove_request(&mut self, request_id: RequestId) -> MultiSigRequestWithSigner {
+        let request = self
+            .requests
+            .remove(&request_id)
+            .unwrap_or_else(|| env::panic_str("No such request"));
+        self.confirmations.remove(&request_id);
+        let key = request.member.to_string();
+        let num_requests = self.num_requests_pk.get(&key).copied().unwrap_or(1);
+        self.num_requests_pk
+            .insert(key, num_requests.saturating_sub(1));
+        request
+    }
+
+    /// Add `member`, adding its access key to the multisig account in `promise`.
+    fn add_member(&mut self, promise: Option<Promise>, member: MultisigMember) -> Option<Promise> {
+        self.members.insert(member.clone());
+        match member {
+            MultisigMember::AccessKey { public_key } => Some(
+                batch(promise, &env::current_account_id()).add_access_key_allowance(
+                    public_key,
+                    Allowance::limited(MEMBER_KEY_ALLOWANCE).unwrap(),
+                    env::current_account_id(),
+                    MULTISIG_METHOD_NAMES,
+                ),
+            ),
+            MultisigMember::Account { .. } => promise,
+        }
+    }
+
+    /// Delete `member`, deleting its access key from the multisig account in `promise`.
+    fn delete_member(
+        &mut self,
+        promise: Option<Promise>,
+        member: MultisigMember,
+    ) -> Option<Promise> {
+        require!(
+            self.members.len() > self.num_confirmations,
+            "Removing given member will make total number of members below number of confirmations"
+        );
+        self.members.remove(&member);
+        match member {
+            MultisigMember::AccessKey { public_key } => {
+                Some(batch(promise, &env::current_account_id()).delete_key(public_key))
+            }
+            MultisigMember::Account { .. } => promise,
+        }
+    }
+
+    /// Run the actions of `request`. Actions changing the multisig configuration apply to its
+    /// state directly, so they must target the multisig account itself.
+    fn execute_request(&mut self, request: MultiSigRequest) -> PromiseOrValue<bool> {
+        let receiver_id = request.receiver_id;
+        let self_request = receiver_id == env::current_account_id();
+        let mut promise = None;
+        for action in request.actions {
+            promise = match action {
+                MultiSigRequestAction::Transfer { amount } => {
+                    Some(batch(promise, &receiver_id).transfer(NearToken::from_yoctonear(amount.0)))
+                }
+                MultiSigRequestAction::CreateAccount => {
+                    Some(batch(promise, &receiver_id).create_account())
+                }
+                MultiSigRequestAction::DeployContract { code } => {
+                    Some(batch(promise, &receiver_id).deploy_contract(code.0))
+                }
+                MultiSigRequestAction::AddMember { member } => {
+                    require!(self_request, "Self request only allowed for member changes");
+                    self.add_member(promise, member)
+                }
+                MultiSigRequestAction::DeleteMember { member } => {
+                    require!(self_request, "Self request only allowed for member changes");
+                    self.delete_member(promise, member)
+                }
+                MultiSigRequestAction::AddKey {
+                    public_key,
+                    permission,
+                } => Some(match permission {
+                    Some(permission) => batch(promise, &receiver_id).add_access_key_allowance(
+                        public_key,
+                        permission
+                            .allowance
+                            .and_then(|allowance| {
+                                Allowance::limited(NearToken::from_yoctonear(allowance.0))
+                            })
+                            .unwrap_or(Allowance::Unlimited),
+                        permission.receiver_id,
+                        permission.method_names.join(","),
+                    ),
+                    None => batch(promise, &receiver_id).add_full_access_key(public_key),
+                }),
+                MultiSigRequestAction::DeleteKey { public_key } => {
+                    Some(batch(promise, &receiver_id).delete_key(public_key))
+                }
+                MultiSigRequestAction::FunctionCall {
+                    method_name,
+                    args,
+                    deposit,
+                    gas,
+                } => Some(batch(promise, &receiver_id).function_call(
+                    method_name,
+                    args.0,
+                    NearToken::from_yoctonear(deposit.0),
+                    Gas::from_gas(gas.0),
+                )),
+                MultiSigRequestAction::SetNumConfirmations { num_confirmations } => {
+                    require!(self_request, "Self request only allowed for config changes");
+                    require!(
+                        self.members.len() >= num_confirmations,
+                        "Members list must be equal or larger than number of confirmations"
+                    );
+                    self.num_confirmations = num_confirmations;
+                    promise
+                }
+                MultiSigRequestAction::SetActiveRequestsLimit {
+                    active_requests_limit,
+                } => {
+                    require!(self_request, "Self request only allowed for config changes");
+                    self.active_requests_limit = active_requests_limit;
+                    promise
+                }
+            };
+        }
+
+        match promise {
+            Some(promise) => PromiseOrValue::Promise(promise),
+            None => PromiseOrValue::Value(true),
+        }
+    }
+}
+
+/// Append to the batch `promise`, or start one against `receiver_id`.
+fn batch(promise: Option<Promise>, receiver_id: &AccountId) -> Promise {
+    promise.unwrap_or_else(|| Promise::new(receiver_id.clone()))
+}
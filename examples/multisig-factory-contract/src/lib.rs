@@ -0,0 +1,95 @@
+//! Port of the `multisig-factory` contract of `near/core-contracts` to near-sdk 5, deployed to
+//! the `multisig.near` account of a sandbox built with `with_standard_contracts()`.
+//!
+//! `create` deploys the multisig contract to a new sub-account of the factory, refunding the
+//! attached deposit if it could not be created.
+
+use near_sdk::serde_json::{self, json};
+use near_sdk::{AccountId, Gas, NearToken, PanicOnDefault, Promise, env, near, require};
+
+/// The multisig contract deployed by `create`, built from `examples/multisig-contract`.
+const MULTISIG_CODE: &[u8] = include_bytes!("../../../workspaces/res/multisig.wasm");
+
+/// Gas attached to the `new` call initializing each multisig contract.
+const MULTISIG_NEW_GAS: Gas = Gas::from_tgas(50);
+
+/// Gas attached to the callback checking whether a multisig contract was created.
+const ON_CREATE_GAS: Gas = Gas::from_tgas(10);
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct MultisigFactory {}
+
+#[near]
+impl MultisigFactory {
+    #[init]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Create the multisig contract `<name>.<factory>` with the given `members`, as accepted
+    /// by its `new` method, funded with the attached deposit.
+    #[payable]
+    pub fn create(
+        &mut self,
+        name: String,
+        members: Vec<serde_json::Value>,
+        num_confirmations: u32,
+    ) -> Promise {
+        let account_id: AccountId = format!("{name}.{}", env::current_account_id())
+            .parse()
+            .unwrap_or_else(|_| env::panic_str("Invalid multisig name"));
+        let amount = env::attached_deposit();
+        Promise::new(account_id.clone())
+            .create_account()
+            .deploy_contract(MULTISIG_CODE)
+            .transfer(amount)
+            .function_call(
+                "new",
+                serde_json::to_vec(&json!({
+                    "members": members,
+                    "num_confirmations": num_confirmations,
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(0),
+                MULTISIG_NEW_GAS,
+            )
+            .then(
+                Promise::new(env::current_account_id()).function_call(
+                    "on_create",
+                    serde_json::to_vec(&json!({
+                        "account_id": account_id,
+                        "amount": amount,
+                        "predecessor_account_id": env::predecessor_account_id(),
+                    }))
+                    .unwrap(),
+                    NearToken::from_yoctonear(0),
+                    ON_CREATE_GAS,
+                ),
+            )
+    }
+
+    /// Refund the deposit of `create` if the multisig contract could not be created.
+    #[private]
+    pub fn on_create(
+        &mut self,
+        account_id: AccountId,
+        amount: NearToken,
+        predecessor_account_id: AccountId,
+    ) -> bool {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected one promise result"
+        );
+        let created = env::promise_result_checked(0, 0).is_ok();
+        if !created {
+            env::log_str(&format!(
+                "Failed to create the multisig contract {account_id}"
+            ));
+            Promise::new(predecessor_account_id)
+                .transfer(amount)
+                .detach();
+        }
+        created
+    }
+}
@@ -5,6 +5,7 @@
 // This is perfect to showcase cron.cat which will schedule calling into contract functions
 // at a set amount of time we supply.
 
+use futures::StreamExt;
 use near_gas::NearGas;
 use near_workspaces::network::Sandbox;
 use near_workspaces::types::NearToken;
@@ -90,9 +91,29 @@ async fn main() -> anyhow::Result<()> {
         .await?
         .into_result()?;
 
+    // Watch the counter contract in the background, so we can check that our agent eventually
+    // calls into `counter.increment` on behalf of the task we scheduled:
+    let mut counter_activity = worker.watch_account(counter_contract.id());
+    let watcher = tokio::spawn(async move {
+        while let Some(activity) = counter_activity.next().await {
+            let activity = activity?;
+            if activity.calls("increment") {
+                return anyhow::Ok(activity);
+            }
+        }
+        anyhow::bail!("counter activity stream ended unexpectedly")
+    });
+
     // Now with all the above setup complete, we can now have the agent run our task:
     run_scheduled_tasks(&worker, &manager_contract, &agent_1).await?;
 
+    let activity = watcher.await??;
+    println!(
+        "`counter.increment` was called by {} in transaction {}",
+        activity.predecessor_id, activity.transaction_hash
+    );
+    assert_eq!(&activity.predecessor_id, manager_contract.id());
+
     Ok(())
 }
 
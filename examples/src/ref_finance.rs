@@ -8,7 +8,7 @@ use near_workspaces::{Account, AccountId, Contract, Worker};
 use near_workspaces::{BlockHeight, DevNetwork};
 use serde_json::json;
 
-const FT_CONTRACT_FILEPATH: &str = "./examples/res/fungible_token.wasm";
+const FT_CONTRACT_FILEPATH: &str = "./workspaces/res/fungible_token.wasm";
 
 /// Contract id of ref-finance on mainnet.
 const REF_FINANCE_ACCOUNT_ID: &str = "v2.ref-finance.near";
@@ -0,0 +1,599 @@
+//! Port of the `staking-pool` contract of `near/core-contracts` to near-sdk 5, deployed by
+//! `Worker::deploy_staking_pool`.
+//!
+//! Delegators deposit NEAR and stake it in exchange for shares of the total stake of the pool,
+//! which grows with the rewards of each epoch minus the fee of the owner. Unstaked NEAR only
+//! becomes withdrawable `NUM_EPOCHS_TO_UNLOCK` epochs after unstaking.
+//!
+//! The share math the original computes with a `U256` from the `uint` crate is done by
+//! `mul_div` here, as that crate no longer builds warning-free.
+
+use near_sdk::json_types::{U64, U128};
+use near_sdk::store::IterableMap;
+use near_sdk::{
+    AccountId, EpochHeight, Gas, NearToken, PanicOnDefault, Promise, PublicKey, env, near, require,
+};
+
+pub type Balance = u128;
+pub type NumStakeShares = Balance;
+
+/// Balance kept by the pool so that rounding can never make the price of a share drop.
+const STAKE_SHARE_PRICE_GUARANTEE_FUND: Balance = 1_000_000_000_000;
+
+/// Number of epochs after which unstaked NEAR can be withdrawn.
+const NUM_EPOCHS_TO_UNLOCK: EpochHeight = 4;
+
+/// Gas attached to the callback checking whether the stake action succeeded.
+const ON_STAKE_ACTION_GAS: Gas = Gas::from_tgas(20);
+
+/// Gas attached to the `vote` call of the owner.
+const VOTE_GAS: Gas = Gas::from_tgas(100);
+
+/// An account delegating to the pool.
+#[near(serializers = [borsh])]
+#[derive(Clone, Default)]
+pub struct Account {
+    /// Unstaked balance, which can be withdrawn or staked.
+    unstaked: Balance,
+    /// Shares of the total stake of the pool.
+    stake_shares: NumStakeShares,
+    /// Epoch from which the unstaked balance can be withdrawn.
+    unstaked_available_epoch_height: EpochHeight,
+}
+
+/// An account delegating to the pool, as returned by the views.
+#[near(serializers = [json])]
+pub struct HumanReadableAccount {
+    pub account_id: AccountId,
+    pub unstaked_balance: U128,
+    pub staked_balance: U128,
+    pub can_withdraw: bool,
+}
+
+/// Fraction of the rewards the owner takes as a fee.
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct RewardFeeFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl RewardFeeFraction {
+    fn assert_valid(&self) {
+        require!(
+            self.denominator != 0,
+            "Denominator must be a positive number"
+        );
+        require!(
+            self.numerator <= self.denominator,
+            "The reward fee must be less or equal to 1"
+        );
+    }
+
+    fn multiply(&self, value: Balance) -> Balance {
+        mul_div(self.numerator.into(), value, self.denominator.into(), false)
+    }
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct StakingContract {
+    owner_id: AccountId,
+    stake_public_key: PublicKey,
+    /// Epoch the pool was last pinged in, distributing its rewards.
+    last_epoch_height: EpochHeight,
+    /// Total balance of the pool, staked or not, when it was last pinged.
+    last_total_balance: Balance,
+    total_staked_balance: Balance,
+    total_stake_shares: NumStakeShares,
+    reward_fee_fraction: RewardFeeFraction,
+    accounts: IterableMap<AccountId, Account>,
+    paused: bool,
+}
+
+#[near]
+impl StakingContract {
+    /// Initialize the pool owned by `owner_id`, staking with `stake_public_key` and taking
+    /// `reward_fee_fraction` of the rewards. The balance of the pool account, minus the share
+    /// price guarantee fund, is staked right away.
+    #[init]
+    pub fn new(
+        owner_id: AccountId,
+        stake_public_key: PublicKey,
+        reward_fee_fraction: RewardFeeFraction,
+    ) -> Self {
+        reward_fee_fraction.assert_valid();
+        let account_balance = env::account_balance().as_yoctonear();
+        let total_staked_balance = account_balance - STAKE_SHARE_PRICE_GUARANTEE_FUND;
+        require!(
+            env::account_locked_balance().is_zero(),
+            "The staking pool shouldn't be staking at the initialization"
+        );
+        let mut this = Self {
+            owner_id,
+            stake_public_key,
+            last_epoch_height: env::epoch_height(),
+            last_total_balance: account_balance,
+            total_staked_balance,
+            total_stake_shares: total_staked_balance,
+            reward_fee_fraction,
+            accounts: IterableMap::new(b"u"),
+            paused: false,
+        };
+        this.internal_restake();
+        this
+    }
+
+    /// Distribute the rewards earned since the last ping, and restake.
+    pub fn ping(&mut self) {
+        if self.internal_ping() {
+            self.internal_restake();
+        }
+    }
+
+    /// Deposit the attached NEAR to the unstaked balance of the predecessor.
+    #[payable]
+    pub fn deposit(&mut self) {
+        let need_to_restake = self.internal_ping();
+        self.internal_deposit();
+        if need_to_restake {
+            self.internal_restake();
+        }
+    }
+
+    /// Deposit the attached NEAR and stake it.
+    #[payable]
+    pub fn deposit_and_stake(&mut self) {
+        self.internal_ping();
+        let amount = self.internal_deposit();
+        self.internal_stake(amount);
+        self.internal_restake();
+    }
+
+    /// Withdraw the entire unstaked balance of the predecessor.
+    pub fn withdraw_all(&mut self) {
+        let need_to_restake = self.internal_ping();
+        let account = self.internal_get_account(&env::predecessor_account_id());
+        self.internal_withdraw(account.unstaked);
+        if need_to_restake {
+            self.internal_restake();
+        }
+    }
+
+    /// Withdraw `amount` of the unstaked balance of the predecessor.
+    pub fn withdraw(&mut self, amount: U128) {
+        let need_to_restake = self.internal_ping();
+        self.internal_withdraw(amount.0);
+        if need_to_restake {
+            self.internal_restake();
+        }
+    }
+
+    /// Stake the entire unstaked balance of the predecessor.
+    pub fn stake_all(&mut self) {
+        self.internal_ping();
+        let account = self.internal_get_account(&env::predecessor_account_id());
+        self.internal_stake(account.unstaked);
+        self.internal_restake();
+    }
+
+    /// Stake `amount` of the unstaked balance of the predecessor.
+    pub fn stake(&mut self, amount: U128) {
+        self.internal_ping();
+        self.internal_stake(amount.0);
+        self.internal_restake();
+    }
+
+    /// Unstake the entire staked balance of the predecessor.
+    pub fn unstake_all(&mut self) {
+        self.internal_ping();
+        let account = self.internal_get_account(&env::predecessor_account_id());
+        let amount = self.staked_amount_from_num_shares_rounded_down(account.stake_shares);
+        self.inner_unstake(amount);
+        self.internal_restake();
+    }
+
+    /// Unstake `amount` of the staked balance of the predecessor.
+    pub fn unstake(&mut self, amount: U128) {
+        self.internal_ping();
+        self.inner_unstake(amount.0);
+        self.internal_restake();
+    }
+
+    /// Unstake everything if the stake action of the last restake failed, such as when the
+    /// stake is too low to become a validator.
+    #[private]
+    pub fn on_stake_action(&mut self) {
+        require!(
+            env::promise_results_count() == 1,
+            "Expected one promise result"
+        );
+        let stake_action_succeeded = env::promise_result_checked(0, 0).is_ok();
+        if !stake_action_succeeded && !env::account_locked_balance().is_zero() {
+            Promise::new(env::current_account_id())
+                .stake(NearToken::from_yoctonear(0), self.stake_public_key.clone())
+                .detach();
+        }
+    }
+
+    pub fn get_account_staked_balance(&self, account_id: AccountId) -> U128 {
+        self.get_account(account_id).staked_balance
+    }
+
+    pub fn get_account_unstaked_balance(&self, account_id: AccountId) -> U128 {
+        self.get_account(account_id).unstaked_balance
+    }
+
+    pub fn is_account_unstaked_balance_available(&self, account_id: AccountId) -> bool {
+        self.get_account(account_id).can_withdraw
+    }
+
+    pub fn get_account_total_balance(&self, account_id: AccountId) -> U128 {
+        let account = self.get_account(account_id);
+        (account.unstaked_balance.0 + account.staked_balance.0).into()
+    }
+
+    pub fn get_total_staked_balance(&self) -> U128 {
+        self.total_staked_balance.into()
+    }
+
+    pub fn get_owner_id(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_reward_fee_fraction(&self) -> RewardFeeFraction {
+        self.reward_fee_fraction.clone()
+    }
+
+    pub fn get_staking_key(&self) -> PublicKey {
+        self.stake_public_key.clone()
+    }
+
+    pub fn is_staking_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn get_account(&self, account_id: AccountId) -> HumanReadableAccount {
+        let account = self.internal_get_account(&account_id);
+        HumanReadableAccount {
+            account_id,
+            unstaked_balance: account.unstaked.into(),
+            staked_balance: self
+                .staked_amount_from_num_shares_rounded_down(account.stake_shares)
+                .into(),
+            can_withdraw: account.unstaked_available_epoch_height <= env::epoch_height(),
+        }
+    }
+
+    pub fn get_number_of_accounts(&self) -> u64 {
+        self.accounts.len() as u64
+    }
+
+    pub fn get_accounts(&self, from_index: U64, limit: U64) -> Vec<HumanReadableAccount> {
+        self.accounts
+            .keys()
+            .skip(from_index.0 as usize)
+            .take(limit.0 as usize)
+            .map(|account_id| self.get_account(account_id.clone()))
+            .collect()
+    }
+
+    /// Stake with `stake_public_key` from now on. Owner only.
+    pub fn update_staking_key(&mut self, stake_public_key: PublicKey) {
+        self.assert_owner();
+        self.stake_public_key = stake_public_key;
+        self.internal_restake();
+    }
+
+    /// Take `reward_fee_fraction` of the rewards from now on. Owner only.
+    pub fn update_reward_fee_fraction(&mut self, reward_fee_fraction: RewardFeeFraction) {
+        self.assert_owner();
+        reward_fee_fraction.assert_valid();
+        let need_to_restake = self.internal_ping();
+        self.reward_fee_fraction = reward_fee_fraction;
+        if need_to_restake {
+            self.internal_restake();
+        }
+    }
+
+    /// Vote on `voting_account_id` as the pool. Owner only.
+    pub fn vote(&mut self, voting_account_id: AccountId, is_vote: bool) -> Promise {
+        self.assert_owner();
+        Promise::new(voting_account_id).function_call(
+            "vote",
+            near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({ "is_vote": is_vote }))
+                .unwrap(),
+            NearToken::from_yoctonear(0),
+            VOTE_GAS,
+        )
+    }
+
+    /// Stop staking, unstaking the entire stake of the pool. Owner only.
+    pub fn pause_staking(&mut self) {
+        self.assert_owner();
+        require!(!self.paused, "The staking is already paused");
+        self.internal_ping();
+        self.paused = true;
+        Promise::new(env::current_account_id())
+            .stake(NearToken::from_yoctonear(0), self.stake_public_key.clone())
+            .detach();
+    }
+
+    /// Resume staking after `pause_staking`. Owner only.
+    pub fn resume_staking(&mut self) {
+        self.assert_owner();
+        require!(self.paused, "The staking is not paused");
+        self.internal_ping();
+        self.paused = false;
+        self.internal_restake();
+    }
+}
+
+impl StakingContract {
+    fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Can only be called by the owner"
+        );
+    }
+
+    /// Stake the total staked balance with the stake key, unless paused.
+    fn internal_restake(&mut self) {
+        if self.paused {
+            return;
+        }
+        Promise::new(env::current_account_id())
+            .stake(
+                NearToken::from_yoctonear(self.total_staked_balance),
+                self.stake_public_key.clone(),
+            )
+            .then(Promise::new(env::current_account_id()).function_call(
+                "on_stake_action",
+                Vec::new(),
+                NearToken::from_yoctonear(0),
+                ON_STAKE_ACTION_GAS,
+            ))
+            .detach();
+    }
+
+    fn internal_deposit(&mut self) -> Balance {
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+        let amount = env::attached_deposit().as_yoctonear();
+        account.unstaked += amount;
+        self.internal_save_account(&account_id, account);
+        self.last_total_balance += amount;
+        amount
+    }
+
+    fn internal_withdraw(&mut self, amount: Balance) {
+        require!(amount > 0, "Withdrawal amount should be positive");
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+        require!(
+            account.unstaked >= amount,
+            "Not enough unstaked balance to withdraw"
+        );
+        require!(
+            account.unstaked_available_epoch_height <= env::epoch_height(),
+            "The unstaked balance is not yet available due to unstaking delay"
+        );
+        account.unstaked -= amount;
+        self.internal_save_account(&account_id, account);
+        Promise::new(account_id)
+            .transfer(NearToken::from_yoctonear(amount))
+            .detach();
+        self.last_total_balance -= amount;
+    }
+
+    fn internal_stake(&mut self, amount: Balance) {
+        require!(amount > 0, "Staking amount should be positive");
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+
+        let num_shares = self.num_shares_from_staked_amount_rounded_down(amount);
+        require!(
+            num_shares > 0,
+            "The calculated number of \"stake\" shares received for staking should be positive"
+        );
+        // The staked amount that will be added to the total, rounded down so that the
+        // remaining rounding error stays in the unstaked balance of the account.
+        let charge_amount = self.staked_amount_from_num_shares_rounded_down(num_shares);
+        require!(
+            charge_amount > 0,
+            "Invariant violation. Calculated staked amount must be positive"
+        );
+        require!(
+            account.unstaked >= charge_amount,
+            "Not enough unstaked balance to stake"
+        );
+        account.unstaked -= charge_amount;
+        account.stake_shares += num_shares;
+        self.internal_save_account(&account_id, account);
+
+        // Rounded up, so that the price of a share never drops, paid by the guarantee fund.
+        let stake_amount = self.staked_amount_from_num_shares_rounded_up(num_shares);
+        self.total_staked_balance += stake_amount;
+        self.total_stake_shares += num_shares;
+    }
+
+    fn inner_unstake(&mut self, amount: Balance) {
+        require!(amount > 0, "Unstaking amount should be positive");
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+        require!(
+            self.total_staked_balance > 0,
+            "The contract doesn't have staked balance"
+        );
+
+        let num_shares = self.num_shares_from_staked_amount_rounded_up(amount);
+        require!(
+            num_shares > 0,
+            "Invariant violation. The calculated number of \"stake\" shares for unstaking should be positive"
+        );
+        require!(
+            account.stake_shares >= num_shares,
+            "Not enough staked balance to unstake"
+        );
+        let receive_amount = self.staked_amount_from_num_shares_rounded_up(num_shares);
+        require!(
+            receive_amount > 0,
+            "Invariant violation. Calculated staked amount must be positive"
+        );
+        account.stake_shares -= num_shares;
+        account.unstaked += receive_amount;
+        account.unstaked_available_epoch_height = env::epoch_height() + NUM_EPOCHS_TO_UNLOCK;
+        self.internal_save_account(&account_id, account);
+
+        // Rounded down, so that the price of a share never drops, paid by the guarantee fund.
+        let unstake_amount = self.staked_amount_from_num_shares_rounded_down(num_shares);
+        self.total_staked_balance -= unstake_amount;
+        self.total_stake_shares -= num_shares;
+    }
+
+    /// Distribute the rewards earned since the last epoch the pool was pinged in, paying the
+    /// fee of the owner in shares. Returns whether a new epoch started.
+    fn internal_ping(&mut self) -> bool {
+        let epoch_height = env::epoch_height();
+        if self.last_epoch_height == epoch_height {
+            return false;
+        }
+        self.last_epoch_height = epoch_height;
+
+        let total_balance = env::account_locked_balance().as_yoctonear()
+            + env::account_balance().as_yoctonear()
+            - env::attached_deposit().as_yoctonear();
+        require!(
+            total_balance >= self.last_total_balance,
+            "The new total balance should not be less than the old total balance"
+        );
+        let total_reward = total_balance - self.last_total_balance;
+        if total_reward > 0 {
+            let owners_fee = self.reward_fee_fraction.multiply(total_reward);
+            // Distributed to every share, including the ones minted for the fee below.
+            self.total_staked_balance += total_reward;
+            let num_shares = self.num_shares_from_staked_amount_rounded_down(owners_fee);
+            if num_shares > 0 {
+                let owner_id = self.owner_id.clone();
+                let mut account = self.internal_get_account(&owner_id);
+                account.stake_shares += num_shares;
+                self.internal_save_account(&owner_id, account);
+                self.total_stake_shares += num_shares;
+            }
+            env::log_str(&format!(
+                "Epoch {epoch_height}: Contract received total rewards of {total_reward} tokens. \
+                 New total staked balance is {}. Total number of shares {}",
+                self.total_staked_balance, self.total_stake_shares,
+            ));
+        }
+
+        self.last_total_balance = total_balance;
+        true
+    }
+
+    fn num_shares_from_staked_amount_rounded_down(&self, amount: Balance) -> NumStakeShares {
+        require!(
+            self.total_staked_balance > 0,
+            "The total staked balance can't be 0"
+        );
+        mul_div(
+            self.total_stake_shares,
+            amount,
+            self.total_staked_balance,
+            false,
+        )
+    }
+
+    fn num_shares_from_staked_amount_rounded_up(&self, amount: Balance) -> NumStakeShares {
+        require!(
+            self.total_staked_balance > 0,
+            "The total staked balance can't be 0"
+        );
+        mul_div(
+            self.total_stake_shares,
+            amount,
+            self.total_staked_balance,
+            true,
+        )
+    }
+
+    fn staked_amount_from_num_shares_rounded_down(&self, num_shares: NumStakeShares) -> Balance {
+        require!(
+            self.total_stake_shares > 0,
+            "The total number of stake shares can't be 0"
+        );
+        mul_div(
+            self.total_staked_balance,
+            num_shares,
+            self.total_stake_shares,
+            false,
+        )
+    }
+
+    fn staked_amount_from_num_shares_rounded_up(&self, num_shares: NumStakeShares) -> Balance {
+        require!(
+            self.total_stake_shares > 0,
+            "The total number of stake shares can't be 0"
+        );
+        mul_div(
+            self.total_staked_balance,
+            num_shares,
+            self.total_stake_shares,
+            true,
+        )
+    }
+
+    fn internal_get_account(&self, account_id: &AccountId) -> Account {
+        self.accounts.get(account_id).cloned().unwrap_or_default()
+    }
+
+    /// Save `account`, removing it once it holds nothing.
+    fn internal_save_account(&mut self, account_id: &AccountId, account: Account) {
+        if account.unstaked > 0 || account.stake_shares > 0 {
+            self.accounts.insert(account_id.clone(), account);
+        } else {
+            self.accounts.remove(account_id);
+        }
+    }
+}
+
+/// `a * b / c`, rounded down or up. The product is computed in 256 bits, as the share math
+/// multiplies balances together.
+fn mul_div(a: u128, b: u128, c: u128, round_up: bool) -> u128 {
+    let (hi, lo) = widening_mul(a, b);
+    let (lo, carry) = if round_up {
+        lo.overflowing_add(c - 1)
+    } else {
+        (lo, false)
+    };
+    let hi = hi + u128::from(carry);
+    require!(hi < c, "Overflow in the share math");
+
+    // Long division of the 256-bit `hi:lo`, keeping the remainder below `c`.
+    let mut remainder = hi;
+    let mut quotient = 0;
+    for bit in (0..128).rev() {
+        let overflow = remainder >> 127 == 1;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        quotient <<= 1;
+        if overflow || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// The 256-bit product of `a` and `b`, as its high and low halves.
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & LOW);
+    let (b_hi, b_lo) = (b >> 64, b & LOW);
+    let low = a_lo * b_lo;
+    let mid_left = a_lo * b_hi;
+    let mid_right = a_hi * b_lo;
+    let mid = (low >> 64) + (mid_left & LOW) + (mid_right & LOW);
+    let lo = (low & LOW) | (mid << 64);
+    let hi = a_hi * b_hi + (mid_left >> 64) + (mid_right >> 64) + (mid >> 64);
+    (hi, lo)
+}
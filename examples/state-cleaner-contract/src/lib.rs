@@ -0,0 +1,24 @@
+//! Contract behind the deletions of `PatchTransaction` in near-workspaces, bundled in
+//! `workspaces/res/state_cleaner.wasm`.
+//!
+//! The sandbox patch mechanism can only add or overwrite records, so near-workspaces patches
+//! this contract in for the length of a transaction removing storage keys, and then patches
+//! the original code back.
+
+use near_sdk::{env, require};
+
+/// Remove a batch of storage keys, given as the borsh serialized `Vec<Vec<u8>>` input. Only
+/// the account itself may remove its keys.
+#[unsafe(no_mangle)]
+pub extern "C" fn clean() {
+    require!(
+        env::predecessor_account_id() == env::current_account_id(),
+        "Only the account itself can remove its state"
+    );
+    let input = env::input().unwrap_or_default();
+    let keys: Vec<Vec<u8>> = near_sdk::borsh::from_slice(&input)
+        .unwrap_or_else(|_| env::panic_str("Invalid list of keys to remove"));
+    for key in keys {
+        env::storage_remove(&key);
+    }
+}
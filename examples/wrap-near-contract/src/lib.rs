@@ -0,0 +1,383 @@
+//! Port of the `w-near` contract of `near/core-contracts` to near-sdk 5, deployed to the
+//! `wrap.near` account of a sandbox built with `with_standard_contracts()`.
+//!
+//! Wrapped NEAR: a NEP-141 fungible token minted one for one by depositing NEAR with
+//! `near_deposit`, and burnt back into NEAR with `near_withdraw`. Accounts are registered
+//! with NEP-145 storage management, or by their first `near_deposit`.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::{self, json};
+use near_sdk::store::LookupMap;
+use near_sdk::{
+    AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue, env, near, require,
+};
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(5);
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_tgas(30);
+
+/// Longest result of `ft_on_transfer` read, which is a `U128` as a JSON string.
+const MAX_RESULT_LEN: usize = 64;
+
+#[near(serializers = [json])]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[near(serializers = [json])]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+#[near(serializers = [json])]
+pub struct FungibleTokenMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+    pub decimals: u8,
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct Contract {
+    accounts: LookupMap<AccountId, u128>,
+    total_supply: u128,
+    account_storage_usage: u64,
+}
+
+#[near]
+impl Contract {
+    #[init]
+    pub fn new() -> Self {
+        let mut this = Self {
+            accounts: LookupMap::new(b"a"),
+            total_supply: 0,
+            account_storage_usage: 0,
+        };
+        let initial_storage_usage = env::storage_usage();
+        let longest_account_id: AccountId = "a".repeat(64).parse().unwrap();
+        this.accounts.insert(longest_account_id.clone(), 0);
+        this.accounts.flush();
+        this.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        this.accounts.remove(&longest_account_id);
+        this.accounts.flush();
+        this
+    }
+
+    /// Wrap the attached NEAR, registering the caller first if needed.
+    #[payable]
+    pub fn near_deposit(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let mut amount = env::attached_deposit().as_yoctonear();
+        if !self.accounts.contains_key(&account_id) {
+            let min = self.storage_cost();
+            require!(
+                amount >= min,
+                "The deposit doesn't cover the storage of the account"
+            );
+            amount -= min;
+            self.accounts.insert(account_id.clone(), 0);
+        }
+        self.deposit(&account_id, amount);
+        self.total_supply += amount;
+        emit(
+            "ft_mint",
+            json!([{ "owner_id": account_id, "amount": U128(amount) }]),
+        );
+    }
+
+    /// Unwrap `amount` back into NEAR.
+    #[payable]
+    pub fn near_withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.withdraw(&account_id, amount.0);
+        self.total_supply -= amount.0;
+        emit(
+            "ft_burn",
+            json!([{ "owner_id": account_id, "amount": amount }]),
+        );
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(amount.0 + 1))
+    }
+
+    #[payable]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.transfer(&sender_id, &receiver_id, amount.0, memo);
+    }
+
+    #[payable]
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        require!(
+            env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL,
+            "More gas is required"
+        );
+        let sender_id = env::predecessor_account_id();
+        self.transfer(&sender_id, &receiver_id, amount.0, memo);
+
+        let receiver_gas = env::prepaid_gas()
+            .saturating_sub(GAS_FOR_FT_TRANSFER_CALL)
+            .saturating_sub(env::used_gas());
+        Promise::new(receiver_id.clone())
+            .function_call(
+                "ft_on_transfer",
+                serde_json::to_vec(&json!({
+                    "sender_id": sender_id,
+                    "amount": amount,
+                    "msg": msg,
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(0),
+                receiver_gas,
+            )
+            .then(
+                Promise::new(env::current_account_id()).function_call(
+                    "ft_resolve_transfer",
+                    serde_json::to_vec(&json!({
+                        "sender_id": sender_id,
+                        "receiver_id": receiver_id,
+                        "amount": amount,
+                    }))
+                    .unwrap(),
+                    NearToken::from_yoctonear(0),
+                    GAS_FOR_RESOLVE_TRANSFER,
+                ),
+            )
+            .into()
+    }
+
+    /// Refund the amount left unused by the receiver of `ft_transfer_call`, returning the
+    /// amount it ended up using.
+    #[private]
+    pub fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let unused = env::promise_result_checked(0, MAX_RESULT_LEN)
+            .ok()
+            .and_then(|value| serde_json::from_slice::<U128>(&value).ok())
+            .map_or(amount.0, |unused| unused.0.min(amount.0));
+        let receiver_balance = self.accounts.get(&receiver_id).copied().unwrap_or(0);
+        let refund = unused.min(receiver_balance);
+        if refund > 0 {
+            self.accounts
+                .insert(receiver_id.clone(), receiver_balance - refund);
+            match self.accounts.get(&sender_id).copied() {
+                Some(balance) => {
+                    self.accounts.insert(sender_id.clone(), balance + refund);
+                    emit(
+                        "ft_transfer",
+                        json!([{
+                            "old_owner_id": receiver_id,
+                            "new_owner_id": sender_id,
+                            "amount": U128(refund),
+                            "memo": "refund",
+                        }]),
+                    );
+                }
+                None => {
+                    self.total_supply -= refund;
+                    emit(
+                        "ft_burn",
+                        json!([{ "owner_id": receiver_id, "amount": U128(refund) }]),
+                    );
+                }
+            }
+        }
+        U128(amount.0 - refund)
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        U128(self.total_supply)
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.accounts.get(&account_id).copied().unwrap_or(0))
+    }
+
+    pub fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: "ft-1.0.0".into(),
+            name: "Wrapped NEAR fungible token".into(),
+            symbol: "wNEAR".into(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        }
+    }
+
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let _ = registration_only;
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let refund = if self.accounts.contains_key(&account_id) {
+            amount
+        } else {
+            let min = self.storage_cost();
+            require!(
+                amount >= min,
+                "The deposit doesn't cover the storage of the account"
+            );
+            self.accounts.insert(account_id, 0);
+            amount - min
+        };
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id())
+                .transfer(NearToken::from_yoctonear(refund))
+                .detach();
+        }
+        self.registered_balance()
+    }
+
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.accounts.contains_key(&account_id),
+            "The account is not registered"
+        );
+        require!(
+            amount.is_none_or(|amount| amount.0 == 0),
+            "The amount is greater than the available storage balance"
+        );
+        self.registered_balance()
+    }
+
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let Some(balance) = self.accounts.get(&account_id).copied() else {
+            return false;
+        };
+        require!(
+            balance == 0 || force == Some(true),
+            "Can't unregister the account with a positive balance without force"
+        );
+        self.accounts.remove(&account_id);
+        if balance > 0 {
+            self.total_supply -= balance;
+            emit(
+                "ft_burn",
+                json!([{ "owner_id": account_id, "amount": U128(balance) }]),
+            );
+        }
+        Promise::new(account_id)
+            .transfer(NearToken::from_yoctonear(self.storage_cost() + 1))
+            .detach();
+        true
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let min = U128(self.storage_cost());
+        StorageBalanceBounds {
+            min,
+            max: Some(min),
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.accounts
+            .contains_key(&account_id)
+            .then(|| self.registered_balance())
+    }
+}
+
+impl Contract {
+    fn storage_cost(&self) -> u128 {
+        env::storage_byte_cost().as_yoctonear() * self.account_storage_usage as u128
+    }
+
+    fn registered_balance(&self) -> StorageBalance {
+        StorageBalance {
+            total: U128(self.storage_cost()),
+            available: U128(0),
+        }
+    }
+
+    fn deposit(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.accounts.get(account_id).copied().unwrap_or_else(|| {
+            env::panic_str(&format!("The account {account_id} is not registered"))
+        });
+        let balance = balance
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        self.accounts.insert(account_id.clone(), balance);
+    }
+
+    fn withdraw(&mut self, account_id: &AccountId, amount: u128) {
+        let balance = self.accounts.get(account_id).copied().unwrap_or_else(|| {
+            env::panic_str(&format!("The account {account_id} is not registered"))
+        });
+        let balance = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+        self.accounts.insert(account_id.clone(), balance);
+    }
+
+    fn transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) {
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(amount > 0, "The amount should be a positive number");
+        self.withdraw(sender_id, amount);
+        self.deposit(receiver_id, amount);
+        emit(
+            "ft_transfer",
+            json!([{
+                "old_owner_id": sender_id,
+                "new_owner_id": receiver_id,
+                "amount": U128(amount),
+                "memo": memo,
+            }]),
+        );
+    }
+}
+
+fn assert_one_yocto() {
+    require!(
+        env::attached_deposit() == NearToken::from_yoctonear(1),
+        "Requires attached deposit of exactly 1 yoctoNEAR"
+    );
+}
+
+/// Log a NEP-141 event, following the NEP-297 format.
+fn emit(event: &str, data: serde_json::Value) {
+    env::log_str(&format!(
+        "EVENT_JSON:{}",
+        json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": event,
+            "data": data,
+        })
+    ));
+}
@@ -0,0 +1,41 @@
+#!/usr/bin/env bash
+# Rebuild the contracts bundled in `workspaces/res` by the `bundled-contracts` feature from
+# their sources under `examples/`, and copy them over the bundled files.
+#
+#     ./scripts/build-bundled-contracts.sh
+#
+# The build is reproducible given the same toolchain and `Cargo.lock`: source paths embedded in
+# the wasm are remapped so they don't depend on where the repository or cargo home live. CI
+# runs this script and fails if the bundled files differ from what it builds, so commit the
+# rebuilt files along with any change to the contract sources.
+#
+# `fungible_token.wasm` and `non_fungible_token.wasm` are the reference tokens of near-sdk and
+# are not built from this repository.
+set -euo pipefail
+
+# Toolchain the bundled contracts are built with. Changing it changes the bundled binaries.
+TOOLCHAIN="${CONTRACTS_TOOLCHAIN:-1.99.1}"
+
+root="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+cd "$root"
+
+export RUSTFLAGS="--remap-path-prefix=$root=/workspace --remap-path-prefix=${CARGO_HOME:-$HOME/.cargo}=/cargo"
+
+# Package and bundled file of each contract. The multisig contract is built before the
+# multisig factory, which embeds it.
+contracts=(
+    "linkdrop-contract linkdrop"
+    "wrap-near-contract wrap_near"
+    "multisig-contract multisig"
+    "multisig-factory-contract multisig_factory"
+    "staking-pool-contract staking_pool"
+    "mock-contract mock"
+    "state-cleaner-contract state_cleaner"
+)
+
+for contract in "${contracts[@]}"; do
+    read -r package file <<<"$contract"
+    cargo "+$TOOLCHAIN" build --locked -p "$package" --profile contract \
+        --target wasm32-unknown-unknown
+    cp "target/wasm32-unknown-unknown/contract/${package//-/_}.wasm" "workspaces/res/$file.wasm"
+done
@@ -21,6 +21,7 @@ pub mod operations;
 pub mod prelude;
 pub mod result;
 pub mod rpc;
+pub mod testing;
 pub mod types;
 
 /// The near_abi_client implementation is currently in flux and we offer a re-export
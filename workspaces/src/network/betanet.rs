@@ -2,6 +2,7 @@ use url::Url;
 
 use crate::network::builder::{FromNetworkBuilder, NetworkBuilder};
 use crate::network::{Info, NetworkClient, NetworkInfo};
+use crate::rpc::cache::StateCache;
 use crate::rpc::client::Client;
 
 use std::path::PathBuf;
@@ -29,8 +30,9 @@ pub struct Betanet {
 impl FromNetworkBuilder for Betanet {
     async fn from_builder<'a>(build: NetworkBuilder<'a, Self>) -> crate::result::Result<Self> {
         let rpc_url = build.rpc_addr.unwrap_or_else(|| RPC_URL.into());
-        let client = Client::new(&rpc_url, build.api_key)?;
-        client.wait_for_rpc().await?;
+        let cache = build.cache_dir.map(|dir| StateCache::new(dir, "betanet"));
+        let client = Client::new(&rpc_url, build.api_key)?.with_cache(cache);
+        client.connect(build.offline).await?;
 
         Ok(Self {
             client,
@@ -1,5 +1,6 @@
 use std::future::{Future, IntoFuture};
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 
 use crate::network::Sandbox;
 use crate::{Network, Worker};
@@ -30,6 +31,10 @@ pub struct NetworkBuilder<'a, T> {
     pub(crate) rpc_addr: Option<String>,
     pub(crate) validator_key: Option<ValidatorKey>,
     pub(crate) api_key: Option<String>,
+    pub(crate) cache_dir: Option<PathBuf>,
+    pub(crate) offline: bool,
+    #[cfg(feature = "bundled-contracts")]
+    pub(crate) standard_contracts: bool,
     _network: PhantomData<T>,
 }
 
@@ -56,6 +61,10 @@ impl<'a, T> NetworkBuilder<'a, T> {
             rpc_addr: None,
             validator_key: None,
             api_key: None,
+            cache_dir: None,
+            offline: false,
+            #[cfg(feature = "bundled-contracts")]
+            standard_contracts: false,
             _network: PhantomData,
         }
     }
@@ -81,6 +90,35 @@ impl<'a, T> NetworkBuilder<'a, T> {
         self.api_key = Some(api_key.into());
         self
     }
+
+    /// Sets the directory to cache the responses of queries made at a pinned block in,
+    /// such as viewing or importing a contract's account, code and state at a specific
+    /// `block_height` or `block_hash`. Since the state of a network at a block never
+    /// changes, these are only fetched once and reused across runs. Tests only making such
+    /// queries can also run [`offline`] once the cache is warmed up.
+    ///
+    /// Note that imports and state views not given a block are pinned to the hash of the
+    /// latest block, so that all of their queries see the same state, and are cached under
+    /// that block too. Such entries are never read again by later runs, and the cache is
+    /// never pruned, so pin the block of the queries meant to be reused.
+    ///
+    /// Caching is not supported for sandbox, as its chain is recreated on every run.
+    ///
+    /// [`offline`]: NetworkBuilder::offline
+    pub fn cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Work offline from the [`cache_dir`], without waiting for the RPC to be reachable
+    /// when building the network. Only queries that were cached will succeed. Building the
+    /// network fails if no `cache_dir` was set.
+    ///
+    /// [`cache_dir`]: NetworkBuilder::cache_dir
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
 }
 
 // So far, only Sandbox makes use of validator_key.
@@ -94,4 +132,31 @@ impl NetworkBuilder<'_, Sandbox> {
         self.validator_key = Some(validator_key);
         self
     }
+
+    /// Preload the sandbox with the contracts found on mainnet that most contracts interact
+    /// with, so that tests don't have to deploy them by hand:
+    /// - `near`: the linkdrop contract behind the `near` top level account, which creates
+    ///   `*.near` accounts with `create_account(new_account_id, new_public_key)` and drops
+    ///   NEAR to keys with `send(public_key)`. It is returned by
+    ///   [`Worker::linkdrop`], and [`Worker::create_near_account`] creates accounts through it.
+    /// - `wrap.near`: the wrapped NEAR fungible token, minting wNEAR with `near_deposit` and
+    ///   burning it back into NEAR with `near_withdraw`. It can be called through
+    ///   [`FungibleToken`](crate::types::ft::FungibleToken) like any other token.
+    /// - `multisig.near`: the multisig factory, deploying multisig contracts to its
+    ///   sub-accounts with `create(name, members, num_confirmations)`. It is returned by
+    ///   [`Worker::multisig_factory`].
+    ///
+    /// The contracts are ports of the `linkdrop`, `w-near`, `multisig2` and `multisig-factory`
+    /// contracts of `near/core-contracts` to near-sdk 5, built from the `examples/*-contract`
+    /// workspace members and bundled in `workspaces/res` with the `bundled-contracts` feature.
+    /// They keep the methods and behavior of the mainnet contracts, but are not byte-identical
+    /// to the code deployed there.
+    ///
+    /// They are deployed and initialized once the sandbox is up, before the worker is
+    /// returned. Without this option, the accessors of these contracts return an error.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn with_standard_contracts(mut self) -> Self {
+        self.standard_contracts = true;
+        self
+    }
 }
@@ -1,6 +1,8 @@
 use crate::network::{Info, NetworkClient, NetworkInfo};
 use crate::result::Result;
+use crate::rpc::cache::StateCache;
 use crate::rpc::client::Client;
+use crate::types::CryptoHash;
 use std::path::PathBuf;
 
 use super::builder::{FromNetworkBuilder, NetworkBuilder};
@@ -17,8 +19,20 @@ impl FromNetworkBuilder for Custom {
         let rpc_url = build
             .rpc_addr
             .expect("rpc address should be provided for custom network");
-        let client = Client::new(&rpc_url, build.api_key)?;
-        client.wait_for_rpc().await?;
+        let url = url::Url::parse(&rpc_url).expect("custom provided url should be valid");
+        // Custom networks are all built under the same name, so tell them apart by their URL,
+        // hashed to fit in a file name. Nodes on the same host but another port or path then
+        // get a cache of their own.
+        let cache = build.cache_dir.map(|dir| {
+            let namespace = format!(
+                "custom-{}-{}",
+                url.host_str().unwrap_or_default(),
+                CryptoHash::hash_bytes(url.as_str().as_bytes())
+            );
+            StateCache::new(dir, &namespace)
+        });
+        let client = Client::new(&rpc_url, build.api_key)?.with_cache(cache);
+        client.connect(build.offline).await?;
 
         Ok(Self {
             client,
@@ -26,7 +40,7 @@ impl FromNetworkBuilder for Custom {
                 name: build.name.into(),
                 root_id: "near".parse().unwrap(),
                 keystore_path: PathBuf::from(".near-credentials/mainnet/"),
-                rpc_url: url::Url::parse(&rpc_url).expect("custom provided url should be valid"),
+                rpc_url: url,
             },
         })
     }
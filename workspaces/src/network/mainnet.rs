@@ -1,5 +1,6 @@
 use crate::network::{Info, NetworkClient, NetworkInfo};
 use crate::result::Result;
+use crate::rpc::cache::StateCache;
 use crate::rpc::client::Client;
 use std::path::PathBuf;
 
@@ -31,8 +32,9 @@ pub struct Mainnet {
 impl FromNetworkBuilder for Mainnet {
     async fn from_builder<'a>(build: NetworkBuilder<'a, Self>) -> Result<Self> {
         let rpc_url = build.rpc_addr.unwrap_or_else(|| RPC_URL.into());
-        let client = Client::new(&rpc_url, build.api_key)?;
-        client.wait_for_rpc().await?;
+        let cache = build.cache_dir.map(|dir| StateCache::new(dir, "mainnet"));
+        let client = Client::new(&rpc_url, build.api_key)?.with_cache(cache);
+        client.connect(build.offline).await?;
 
         Ok(Self {
             client,
@@ -18,8 +18,45 @@ use crate::rpc::client::Client;
 use crate::types::{AccountId, InMemorySigner, NearToken, SecretKey};
 use crate::{Account, Contract, Network, Worker};
 
+#[cfg(feature = "bundled-contracts")]
+use {
+    crate::error::ErrorKind,
+    crate::rpc::client::send_batch_tx_and_retry,
+    crate::types::Gas,
+    near_primitives::account::{AccessKey, AccessKeyPermission},
+    near_primitives::transaction::{
+        Action, AddKeyAction, CreateAccountAction, DeployContractAction, FunctionCallAction,
+        TransferAction,
+    },
+};
+
 // Constant taken from nearcore crate to avoid dependency
 const DEFAULT_DEPOSIT: NearToken = NearToken::from_near(100);
+
+/// Linkdrop contract deployed to the `near` account by
+/// [`NetworkBuilder::with_standard_contracts`].
+#[cfg(feature = "bundled-contracts")]
+const LINKDROP_WASM: &[u8] = include_bytes!("../../res/linkdrop.wasm");
+
+/// Wrapped NEAR contract deployed to the `wrap.near` account by
+/// [`NetworkBuilder::with_standard_contracts`].
+#[cfg(feature = "bundled-contracts")]
+const WRAP_NEAR_WASM: &[u8] = include_bytes!("../../res/wrap_near.wasm");
+
+/// Multisig factory contract deployed to the `multisig.near` account by
+/// [`NetworkBuilder::with_standard_contracts`].
+#[cfg(feature = "bundled-contracts")]
+const MULTISIG_FACTORY_WASM: &[u8] = include_bytes!("../../res/multisig_factory.wasm");
+
+/// Balance of the `near` account, which pays for the storage of the accounts created by
+/// its linkdrop contract.
+#[cfg(feature = "bundled-contracts")]
+const NEAR_ACCOUNT_DEPOSIT: NearToken = NearToken::from_near(10_000);
+
+/// Gas attached to the `new` call initializing each standard contract.
+#[cfg(feature = "bundled-contracts")]
+const STANDARD_CONTRACT_INIT_GAS: Gas = Gas::from_tgas(30);
+
 /// Local sandboxed environment/network
 ///
 /// Can be used to test without interacting with
@@ -32,6 +69,8 @@ pub struct Sandbox {
     client: Client,
     info: Info,
     version: Option<String>,
+    #[cfg(feature = "bundled-contracts")]
+    standard_contracts: bool,
 }
 
 impl Sandbox {
@@ -56,6 +95,16 @@ impl Sandbox {
         build: NetworkBuilder<'_, Self>,
         version: &str,
     ) -> Result<Self> {
+        if let Some(cache_dir) = &build.cache_dir {
+            return Err(SandboxErrorCode::InitFailure.message(format!(
+                "cache_dir={cache_dir:?} is not supported for sandbox, as its state is recreated on every run."
+            )));
+        }
+        if build.offline {
+            return Err(SandboxErrorCode::InitFailure
+                .message("offline is not supported for sandbox, as it runs locally"));
+        }
+
         // Check the conditions of the provided rpc_url and validator_key
         let server = match (build.rpc_addr, build.validator_key) {
             // Connect to a provided sandbox:
@@ -89,13 +138,133 @@ impl Sandbox {
             rpc_url: url::Url::parse(&server.rpc_addr()).expect("url is hardcoded"),
         };
 
-        Ok(Self {
+        let sandbox = Self {
             server,
             client,
             info,
             version: Some(version.to_string()),
-        })
+            #[cfg(feature = "bundled-contracts")]
+            standard_contracts: build.standard_contracts,
+        };
+        #[cfg(feature = "bundled-contracts")]
+        if sandbox.standard_contracts {
+            sandbox.deploy_standard_contracts().await?;
+        }
+
+        Ok(sandbox)
     }
+
+    /// Signer of the `near` account created by [`NetworkBuilder::with_standard_contracts`].
+    #[cfg(feature = "bundled-contracts")]
+    pub(crate) fn near_signer(&self) -> Result<InMemorySigner> {
+        self.standard_signer("near")
+    }
+
+    /// Signer of the `wrap.near` account created by
+    /// [`NetworkBuilder::with_standard_contracts`].
+    #[cfg(feature = "bundled-contracts")]
+    pub(crate) fn wrap_near_signer(&self) -> Result<InMemorySigner> {
+        self.standard_signer("wrap.near")
+    }
+
+    /// Signer of the `multisig.near` account created by
+    /// [`NetworkBuilder::with_standard_contracts`].
+    #[cfg(feature = "bundled-contracts")]
+    pub(crate) fn multisig_factory_signer(&self) -> Result<InMemorySigner> {
+        self.standard_signer("multisig.near")
+    }
+
+    /// The standard contract accounts share the key of the registrar that created them. Fails
+    /// if the sandbox was built without them, as the accounts don't exist then.
+    #[cfg(feature = "bundled-contracts")]
+    fn standard_signer(&self, id: &str) -> Result<InMemorySigner> {
+        if !self.standard_contracts {
+            return Err(ErrorKind::Other.message(format!(
+                "the standard contract account `{id}` requires the sandbox to be built with `with_standard_contracts()`"
+            )));
+        }
+        let registrar = self.registrar_signer()?;
+        let id = id.parse().expect("standard account ids are valid");
+        Ok(InMemorySigner::from_secret_key(id, registrar.secret_key))
+    }
+
+    /// Create the `near` account running the linkdrop contract, then `wrap.near` running the
+    /// wrapped NEAR contract and `multisig.near` running the multisig factory, each initialized
+    /// with `new()`.
+    #[cfg(feature = "bundled-contracts")]
+    async fn deploy_standard_contracts(&self) -> Result<()> {
+        let registrar = self.registrar_signer()?;
+        let near = self.near_signer()?;
+        let wrap_near = self.wrap_near_signer()?;
+        let multisig_factory = self.multisig_factory_signer()?;
+
+        for (signer, id, deposit, wasm) in [
+            (
+                &registrar,
+                &near.account_id,
+                NEAR_ACCOUNT_DEPOSIT,
+                LINKDROP_WASM,
+            ),
+            (
+                &near,
+                &wrap_near.account_id,
+                DEFAULT_DEPOSIT,
+                WRAP_NEAR_WASM,
+            ),
+            (
+                &near,
+                &multisig_factory.account_id,
+                DEFAULT_DEPOSIT,
+                MULTISIG_FACTORY_WASM,
+            ),
+        ] {
+            let outcome = send_batch_tx_and_retry(
+                &self.client,
+                signer,
+                id,
+                standard_contract_actions(&near, deposit, wasm),
+            )
+            .await?;
+            ExecutionFinalResult::from_view(outcome)
+                .into_result()
+                .map_err(|err| {
+                    SandboxErrorCode::InitFailure
+                        .full(format!("failed to deploy the standard contract {id}"), err)
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Actions creating a standard contract account, holding the key of `signer`, then deploying
+/// `wasm` to it and initializing it.
+#[cfg(feature = "bundled-contracts")]
+fn standard_contract_actions(
+    signer: &InMemorySigner,
+    deposit: NearToken,
+    wasm: &[u8],
+) -> Vec<Action> {
+    vec![
+        CreateAccountAction {}.into(),
+        AddKeyAction {
+            public_key: signer.secret_key.public_key().into(),
+            access_key: AccessKey {
+                nonce: 0,
+                permission: AccessKeyPermission::FullAccess,
+            },
+        }
+        .into(),
+        TransferAction { deposit }.into(),
+        DeployContractAction { code: wasm.into() }.into(),
+        FunctionCallAction {
+            method_name: "new".into(),
+            args: b"{}".to_vec(),
+            gas: near_primitives::gas::Gas::from_gas(STANDARD_CONTRACT_INIT_GAS.as_gas()),
+            deposit: NearToken::from_near(0),
+        }
+        .into(),
+    ]
 }
 
 impl std::fmt::Debug for Sandbox {
@@ -11,6 +11,7 @@ use crate::network::Info;
 use crate::network::builder::{FromNetworkBuilder, NetworkBuilder};
 use crate::network::{NetworkClient, NetworkInfo, RootAccountSubaccountCreator};
 use crate::result::{Execution, ExecutionDetails, ExecutionFinalResult, ExecutionOutcome, Result};
+use crate::rpc::cache::StateCache;
 use crate::rpc::{client::Client, tool};
 use crate::types::{AccountId, InMemorySigner, NearToken, SecretKey};
 use crate::{Account, Contract, CryptoHash, Network, Worker};
@@ -41,8 +42,9 @@ pub struct Testnet {
 impl FromNetworkBuilder for Testnet {
     async fn from_builder<'a>(build: NetworkBuilder<'a, Self>) -> Result<Self> {
         let rpc_url = build.rpc_addr.unwrap_or_else(|| RPC_URL.into());
-        let client = Client::new(&rpc_url, build.api_key)?;
-        client.wait_for_rpc().await?;
+        let cache = build.cache_dir.map(|dir| StateCache::new(dir, "testnet"));
+        let client = Client::new(&rpc_url, build.api_key)?.with_cache(cache);
+        client.connect(build.offline).await?;
 
         Ok(Self {
             client,
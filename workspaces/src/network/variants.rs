@@ -8,6 +8,14 @@ use crate::types::{AccountId, KeyType, SecretKey};
 use crate::{Account, Contract, Worker};
 use async_trait::async_trait;
 
+#[cfg(feature = "bundled-contracts")]
+use crate::{
+    testing::MockContract,
+    types::ft::{FUNGIBLE_TOKEN_WASM, FungibleToken, FungibleTokenMetadata},
+    types::nft::{NON_FUNGIBLE_TOKEN_WASM, NftContractMetadata, NonFungibleToken},
+    types::staking::{RewardFeeFraction, STAKING_POOL_WASM, StakingPool},
+};
+
 pub(crate) const DEV_ACCOUNT_SEED: &str = "testificate";
 
 pub trait NetworkClient {
@@ -203,6 +211,79 @@ where
             .await?;
         Ok(contract.into_result()?)
     }
+
+    /// Deploys a [`MockContract`] to a new dev account, whose methods and their responses are
+    /// then programmed with [`MockContract::on`].
+    #[cfg(feature = "bundled-contracts")]
+    pub async fn deploy_mock(&self) -> Result<MockContract> {
+        let contract = self.dev_deploy(&MockContract::wasm(&[])?).await?;
+        Ok(MockContract::new(contract))
+    }
+
+    /// Deploys the bundled reference fungible token to a new dev account, minting
+    /// `total_supply` tokens to the token account itself. Tokens can then be handed out by
+    /// transferring them from [`Contract::as_account`] of [`FungibleToken::contract`].
+    #[cfg(feature = "bundled-contracts")]
+    pub async fn deploy_ft(
+        &self,
+        total_supply: u128,
+        metadata: FungibleTokenMetadata,
+    ) -> Result<FungibleToken> {
+        let contract = self.dev_deploy(FUNGIBLE_TOKEN_WASM).await?;
+        contract
+            .call("new")
+            .args_json(serde_json::json!({
+                "owner_id": contract.id(),
+                "total_supply": total_supply.to_string(),
+                "metadata": metadata,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(FungibleToken::new(contract))
+    }
+
+    /// Deploys the bundled reference non-fungible token to a new dev account, owned by the
+    /// token account itself. Tokens can then be minted by [`Contract::as_account`] of
+    /// [`NonFungibleToken::contract`].
+    #[cfg(feature = "bundled-contracts")]
+    pub async fn deploy_nft(&self, metadata: NftContractMetadata) -> Result<NonFungibleToken> {
+        let contract = self.dev_deploy(NON_FUNGIBLE_TOKEN_WASM).await?;
+        contract
+            .call("new")
+            .args_json(serde_json::json!({
+                "owner_id": contract.id(),
+                "metadata": metadata,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(NonFungibleToken::new(contract))
+    }
+
+    /// Deploys the bundled core staking pool to a new dev account, owned by `owner_id` and
+    /// taking `reward_fee` of the rewards. The pool stakes with a newly generated key, whose
+    /// secret is kept in [`StakingPool::stake_secret_key`].
+    #[cfg(feature = "bundled-contracts")]
+    pub async fn deploy_staking_pool(
+        &self,
+        owner_id: &AccountId,
+        reward_fee: RewardFeeFraction,
+    ) -> Result<StakingPool> {
+        let contract = self.dev_deploy(STAKING_POOL_WASM).await?;
+        let stake_key = SecretKey::from_random(KeyType::ED25519);
+        contract
+            .call("new")
+            .args_json(serde_json::json!({
+                "owner_id": owner_id,
+                "stake_public_key": stake_key.public_key(),
+                "reward_fee_fraction": reward_fee,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(StakingPool::with_stake_secret_key(contract, stake_key))
+    }
 }
 
 /// Network trait specifies the functionality of a network type such as mainnet, testnet or any
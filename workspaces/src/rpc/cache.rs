@@ -0,0 +1,101 @@
+//! On-disk cache for the responses of queries made against a network at a pinned block.
+//!
+//! The state of a network at a specific block never changes, so responses to queries such
+//! as viewing the account, code or state of a contract at a pinned block can be saved and
+//! reused across runs. Queries against the latest block or a finality are never cached.
+//!
+//! Imports and paged state views not given a block resolve the latest block to its hash
+//! first, so their queries are made at a pinned block and cached as well. Entries are never
+//! evicted, so the cache keeps growing with every such run.
+
+use std::path::{Path, PathBuf};
+
+use near_jsonrpc_client::methods::RpcMethod;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::Digest;
+
+/// Query request types whose responses only depend on the block they were made at.
+const CACHEABLE_REQUEST_TYPES: &[&str] = &["view_account", "view_code", "view_state"];
+
+/// Cache of query responses, laid out on disk as:
+/// `{dir}/{network}/{account_id}/{block_id}-{request_type}-{hash of the full request}.json`
+#[derive(Clone, Debug)]
+pub(crate) struct StateCache {
+    dir: PathBuf,
+}
+
+impl StateCache {
+    pub(crate) fn new(dir: impl Into<PathBuf>, network: &str) -> Self {
+        Self {
+            dir: dir.into().join(network),
+        }
+    }
+
+    /// Path to the cache entry of `method`, or `None` if the method is not cacheable.
+    pub(crate) fn path<M: RpcMethod>(&self, method: &M) -> Option<PathBuf> {
+        if method.method_name() != "query" {
+            return None;
+        }
+
+        let params = method.params().ok()?;
+        let block_id = match params.get("block_id")? {
+            serde_json::Value::Number(height) => height.to_string(),
+            serde_json::Value::String(hash) => hash.clone(),
+            _ => return None,
+        };
+        let request_type = params.get("request_type")?.as_str()?;
+        if !CACHEABLE_REQUEST_TYPES.contains(&request_type) {
+            return None;
+        }
+        let account_id = params.get("account_id")?.as_str()?;
+
+        let hash = sha2::Sha256::digest(params.to_string().as_bytes());
+        let hash = bs58::encode(&hash[..8]).into_string();
+        Some(
+            self.dir
+                .join(account_id)
+                .join(format!("{block_id}-{request_type}-{hash}.json")),
+        )
+    }
+
+    /// Read the response saved at `path`. Entries that are missing or cannot be read are
+    /// treated as not being cached.
+    pub(crate) async fn get<R: DeserializeOwned>(&self, path: &Path) -> Option<R> {
+        let bytes = tokio::fs::read(path).await.ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(resp) => Some(resp),
+            Err(err) => {
+                tracing::warn!(
+                    target: "workspaces",
+                    "Ignoring unreadable cache entry {path:?}: {err}"
+                );
+                None
+            }
+        }
+    }
+
+    /// Save the response to `path`. Failing to do so is not an error, since the response
+    /// can always be fetched from the network again.
+    pub(crate) async fn put<R: Serialize>(&self, path: &Path, resp: &R) {
+        if let Err(err) = Self::write(path, resp).await {
+            tracing::warn!(
+                target: "workspaces",
+                "Failed to write cache entry {path:?}: {err}"
+            );
+        }
+    }
+
+    async fn write<R: Serialize>(path: &Path, resp: &R) -> std::io::Result<()> {
+        let bytes = serde_json::to_vec(resp)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a temporary file first, so that concurrent readers never see a partially
+        // written entry.
+        let tmp = path.with_extension(format!("{}.tmp", rand::random::<u64>()));
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+}
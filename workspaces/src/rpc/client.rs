@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use crate::types::NearToken;
 use near_gas::NearGas;
+use serde::Serialize;
 use tokio::sync::RwLock;
 use tokio_retry::Retry;
 use tokio_retry::strategy::{ExponentialBackoff, jitter};
@@ -12,6 +13,9 @@ use tokio_retry::strategy::{ExponentialBackoff, jitter};
 use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
 use near_jsonrpc_client::methods::tx::{RpcTransactionError, RpcTransactionResponse};
 use near_jsonrpc_client::{JsonRpcClient, MethodCallResult, methods};
+use near_jsonrpc_primitives::types::changes::{
+    RpcStateChangesInBlockByTypeResponse, RpcStateChangesInBlockResponse,
+};
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_primitives::account::{AccessKey, AccessKeyPermission};
 use near_primitives::errors::InvalidTxError;
@@ -22,26 +26,24 @@ use near_primitives::transaction::{
 };
 use near_primitives::types::{BlockReference, Finality, Gas};
 use near_primitives::views::{
-    AccessKeyView, BlockView, FinalExecutionOutcomeView, QueryRequest, StatusResponse,
-    TxExecutionStatus,
+    AccessKeyView, BlockView, FinalExecutionOutcomeView, QueryRequest, StateChangesRequestView,
+    StatusResponse, TxExecutionStatus,
 };
 
 #[cfg(feature = "experimental")]
 use {
     near_chain_configs::{GenesisConfig, ProtocolConfigView},
-    near_jsonrpc_primitives::types::{
-        changes::RpcStateChangesInBlockByTypeResponse, changes::RpcStateChangesInBlockResponse,
-        receipts::ReceiptReference, transactions::TransactionInfo,
-    },
+    near_jsonrpc_primitives::types::{receipts::ReceiptReference, transactions::TransactionInfo},
     near_primitives::{
         types::MaybeBlockId,
-        views::{ReceiptView, StateChangesRequestView, validator_stake_view::ValidatorStakeView},
+        views::{ReceiptView, validator_stake_view::ValidatorStakeView},
     },
 };
 
 use crate::error::{Error, ErrorKind, RpcErrorCode};
 use crate::operations::TransactionStatus;
 use crate::result::Result;
+use crate::rpc::cache::StateCache;
 use crate::types::{AccountId, InMemorySigner, Nonce, PublicKey};
 use crate::{Network, Worker};
 
@@ -55,6 +57,8 @@ pub struct Client {
     rpc_client: JsonRpcClient,
     /// AccessKey nonces to reference when sending transactions.
     pub(crate) access_key_nonces: RwLock<HashMap<(AccountId, near_crypto::PublicKey), AtomicU64>>,
+    /// Cache of query responses at pinned blocks, if one was configured for the network.
+    cache: Option<StateCache>,
 }
 
 impl Client {
@@ -71,32 +75,41 @@ impl Client {
             rpc_client,
             rpc_addr: rpc_addr.into(),
             access_key_nonces: RwLock::new(HashMap::new()),
+            cache: None,
         })
     }
 
+    pub(crate) fn with_cache(mut self, cache: Option<StateCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     pub(crate) async fn query_broadcast_tx(
         &self,
         method: &methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest,
     ) -> MethodCallResult<FinalExecutionOutcomeView, RpcTransactionError> {
         retry(|| async {
             let result = self.rpc_client.call(method).await;
+            // Only the transaction is logged rather than the whole request, leaving its
+            // signature out of the logs.
+            let transaction = &method.signed_transaction.transaction;
             match &result {
                 Ok(response) => {
                     // When user sets logging level to INFO we only print one-liners with submitted
                     // actions and the resulting status. If the level is DEBUG or lower, we print
-                    // the entire request and response structures.
+                    // the entire transaction and response structures.
                     if tracing::level_enabled!(tracing::Level::DEBUG) {
                         tracing::debug!(
                             target: "workspaces",
-                            "Calling RPC method {:?} succeeded with {:?}",
-                            method,
+                            "Submitting transaction {:?} succeeded with {:?}",
+                            transaction,
                             response
                         );
                     } else {
                         tracing::info!(
                             target: "workspaces",
                             "Submitting transaction with actions {:?} succeeded with status {:?}",
-                            method.signed_transaction.transaction.actions(),
+                            transaction.actions(),
                             response.status
                         );
                     }
@@ -104,8 +117,40 @@ impl Client {
                 Err(error) => {
                     tracing::error!(
                         target: "workspaces",
-                        "Calling RPC method {:?} resulted in error {:?}",
-                        method,
+                        "Submitting transaction {:?} resulted in error {:?}",
+                        transaction,
+                        error
+                    );
+                }
+            };
+            result
+        })
+        .await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub(crate) async fn query_broadcast_tx_async(
+        &self,
+        method: &methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest,
+    ) -> MethodCallResult<CryptoHash, methods::broadcast_tx_async::RpcBroadcastTxAsyncError> {
+        retry(|| async {
+            let result = self.rpc_client.call(method).await;
+            // Same as `query_broadcast_tx`, the signature is left out of the logs.
+            let transaction = &method.signed_transaction.transaction;
+            match &result {
+                Ok(hash) => {
+                    tracing::debug!(
+                        target: "workspaces",
+                        "Submitting transaction {:?} asynchronously succeeded with hash {}",
+                        transaction,
+                        hash
+                    );
+                }
+                Err(error) => {
+                    tracing::error!(
+                        target: "workspaces",
+                        "Submitting transaction {:?} asynchronously resulted in error {:?}",
+                        transaction,
                         error
                     );
                 }
@@ -141,6 +186,33 @@ impl Client {
         .await
     }
 
+    /// Same as [`Client::query`], but responses of queries made at a pinned block are read
+    /// from and saved to the on-disk cache of the network, if one was configured.
+    #[allow(clippy::result_large_err)]
+    pub(crate) async fn query_cached<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod + Debug + Send + Sync,
+        M::Response: Debug + Send + Serialize,
+        M::Error: Debug + Send,
+    {
+        let Some((cache, path)) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| Some((cache, cache.path(&method)?)))
+        else {
+            return self.query(method).await;
+        };
+
+        if let Some(resp) = cache.get(&path).await {
+            tracing::debug!(target: "workspaces", "Read {:?} from cache {:?}", method, path);
+            return Ok(resp);
+        }
+
+        let resp = self.query(method).await?;
+        cache.put(&path, &resp).await;
+        Ok(resp)
+    }
+
     async fn send_tx_and_retry(
         &self,
         signer: &InMemorySigner,
@@ -337,10 +409,25 @@ impl Client {
             })?;
         Ok(())
     }
-}
 
-#[cfg(feature = "experimental")]
-impl Client {
+    /// Wait for the RPC to be reachable, unless working `offline` from the cache, in which
+    /// case only queries that were cached will succeed.
+    pub(crate) async fn connect(&self, offline: bool) -> Result<()> {
+        if !offline {
+            return self.wait_for_rpc().await;
+        }
+        if self.cache.is_none() {
+            return Err(RpcErrorCode::ConnectionFailure
+                .message("working offline requires a cache_dir to serve queries from"));
+        }
+
+        tracing::info!(
+            target: "workspaces",
+            "Working offline, only cached queries will succeed"
+        );
+        Ok(())
+    }
+
     pub(crate) async fn changes_in_block(
         &self,
         block_reference: BlockReference,
@@ -375,7 +462,10 @@ impl Client {
             .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
         Ok(resp)
     }
+}
 
+#[cfg(feature = "experimental")]
+impl Client {
     pub(crate) async fn genesis_config(&self) -> Result<GenesisConfig> {
         let resp = self
             .rpc_client
@@ -590,7 +680,7 @@ pub(crate) async fn send_batch_tx_async_and_retry(
         let (block_hash, nonce) = fetch_tx_nonce(worker.client(), &cache_key).await?;
         let hash = worker
             .client()
-            .query(&methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
+            .query_broadcast_tx_async(&methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
                 signed_transaction: SignedTransaction::from_actions(
                     nonce,
                     signer.account_id.clone(),
@@ -1,7 +1,10 @@
+pub(crate) mod cache;
 pub(crate) mod client;
+pub(crate) mod records;
 pub(crate) mod tool;
 
 pub mod patch;
 pub mod query;
+pub mod stream;
 
 pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
@@ -1,14 +1,57 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::time::Duration;
+
+use futures::StreamExt;
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
+use near_jsonrpc_client::methods;
+use near_jsonrpc_client::methods::query::{RpcQueryError, RpcQueryResponse};
 use near_jsonrpc_client::methods::sandbox_patch_state::RpcSandboxPatchStateRequest;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::borsh::{self, BorshSerialize};
 use near_primitives::state_record::StateRecord;
 use near_primitives::types::{BlockId, BlockReference};
+use near_primitives::views::QueryRequest;
 use near_token::NearToken;
 
-use crate::error::SandboxErrorCode;
+use crate::error::{Error, ErrorKind, RpcErrorCode, SandboxErrorCode};
 use crate::network::{DEV_ACCOUNT_SEED, Sandbox};
+use crate::operations::Function;
+use crate::rpc::stream::{DEFAULT_STATE_PAGE_SIZE, StatePage, StatePageStream};
 use crate::types::account::{AccountDetails, ContractState};
-use crate::types::{BlockHeight, KeyType, PublicKey, SecretKey};
+use crate::types::{BlockHeight, Gas, KeyType, PublicKey, SecretKey};
 use crate::{AccessKey, AccountDetailsPatch, Result};
-use crate::{AccountId, Contract, CryptoHash, InMemorySigner, Network, Worker};
+use crate::{Account, AccountId, Contract, CryptoHash, InMemorySigner, Network, Worker};
+
+/// Default maximum size of a single patch request made when patching many records at once,
+/// leaving headroom under the default 10 MiB JSON payload limit of the RPC.
+pub(crate) const DEFAULT_PATCH_PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
+
+/// Maximum number of storage keys removed by a single call to [`STATE_CLEANER_WASM`], so
+/// that the call stays within the gas limit even when the values removed are large.
+const MAX_STATE_DELETIONS_PER_CALL: usize = 100;
+
+/// Gas attached to each call to [`STATE_CLEANER_WASM`], one per transaction.
+const STATE_DELETION_GAS: Gas = Gas::from_tgas(250);
+
+/// Contract temporarily patched in to remove storage keys, since the sandbox patch mechanism
+/// can only add or overwrite records. Calling `clean` removes the borsh serialized list of
+/// keys given as input. Built from `examples/state-cleaner-contract`.
+#[cfg(feature = "bundled-contracts")]
+const STATE_CLEANER_WASM: &[u8] = include_bytes!("../../res/state_cleaner.wasm");
+
+/// Without the bundled contracts, state can't be deleted, so the state cleaner is never
+/// deployed.
+#[cfg(not(feature = "bundled-contracts"))]
+const STATE_CLEANER_WASM: &[u8] = &[];
+
+/// Number of times to retry viewing a page of state before giving up on the import.
+const MAX_STATE_PAGE_RETRIES: u32 = 5;
+
+/// Default number of levels deep to follow accounts discovered when importing contracts.
+const DEFAULT_MAX_DISCOVERY_DEPTH: usize = 2;
+
+/// Default maximum number of accounts discovered when importing contracts to be imported.
+const DEFAULT_MAX_DISCOVERED_ACCOUNTS: usize = 50;
 
 /// A [`Transaction`]-like object with details about importing a contract from a network
 /// into sandbox local network.
@@ -34,6 +77,12 @@ pub struct ImportContractTransaction<'a> {
 
     /// AccountId if specified, will be the destination account to clone the contract to.
     into_account_id: Option<AccountId>,
+
+    /// Number of keys to view at a time when importing the state.
+    state_page_size: u32,
+
+    /// Maximum number of bytes of state to patch into sandbox in a single request.
+    patch_batch_size: usize,
 }
 
 impl<'a> ImportContractTransaction<'a> {
@@ -50,6 +99,8 @@ impl<'a> ImportContractTransaction<'a> {
             initial_balance: None,
             block_ref: None,
             into_account_id: None,
+            state_page_size: DEFAULT_STATE_PAGE_SIZE.get(),
+            patch_batch_size: DEFAULT_PATCH_PAYLOAD_SIZE,
         }
     }
 
@@ -74,14 +125,39 @@ impl<'a> ImportContractTransaction<'a> {
 
     /// Along with importing the contract code, this will import the state from the
     /// contract itself. This is useful for testing current network state or state
-    /// at a specific block. Note that there is a limit of 50kb of state data that
-    /// can be pulled down using the usual RPC service. To get beyond this, our own
-    /// RPC node has to be spun up and used instead.
+    /// at a specific block.
+    ///
+    /// The state is viewed a page at a time, so states larger than what the RPC allows
+    /// in a single view can be imported. Pages that fail to be viewed are retried, and the
+    /// import resumes from the last key that was viewed. The state is then patched into
+    /// sandbox in batches of at most [`patch_batch_size`] bytes.
+    ///
+    /// [`patch_batch_size`]: ImportContractTransaction::patch_batch_size
     pub fn with_data(mut self) -> Self {
         self.import_data = true;
         self
     }
 
+    /// Specify the maximum number of keys to view at a time when importing the state with
+    /// [`with_data`]. Lower this if the network rejects views for being too large. Defaults
+    /// to 500.
+    ///
+    /// [`with_data`]: ImportContractTransaction::with_data
+    pub fn state_page_size(mut self, page_size: u32) -> Self {
+        self.state_page_size = page_size;
+        self
+    }
+
+    /// Specify the maximum size in bytes of a single request patching state into sandbox
+    /// when importing the state with [`with_data`]. This keeps each request under the
+    /// `json_payload_max_size` of the sandbox node. Defaults to 8 MiB.
+    ///
+    /// [`with_data`]: ImportContractTransaction::with_data
+    pub fn patch_batch_size(mut self, bytes: usize) -> Self {
+        self.patch_batch_size = bytes.max(1);
+        self
+    }
+
     /// Specifies the balance of the contract. This will override the balance currently
     /// on the network this transaction is importing from.
     pub fn initial_balance(mut self, initial_balance: NearToken) -> Self {
@@ -97,15 +173,45 @@ impl<'a> ImportContractTransaction<'a> {
         self
     }
 
+    pub(crate) fn block_reference(mut self, block_ref: BlockReference) -> Self {
+        self.block_ref = Some(block_ref);
+        self
+    }
+
     /// Process the transaction, and return the result of the execution.
+    ///
+    /// The state is patched into sandbox in several requests before the account. If importing
+    /// fails part way through, the account is not patched, but the state records already
+    /// patched stay behind in sandbox.
     pub async fn transact(self) -> Result<Contract> {
+        self.transact_with(|_| {}).await
+    }
+
+    /// Same as [`transact`], but with `on_page` being called on every page of state
+    /// imported when importing with [`with_data`]. Like [`transact`], a failed import can
+    /// leave orphaned state records behind.
+    ///
+    /// [`transact`]: ImportContractTransaction::transact
+    /// [`with_data`]: ImportContractTransaction::with_data
+    pub(crate) async fn transact_with(
+        self,
+        on_page: impl FnMut(&StatePage) + Send,
+    ) -> Result<Contract> {
         let from_account_id = self.account_id;
         let into_account_id = self.into_account_id.as_ref().unwrap_or(from_account_id);
 
         let sk = SecretKey::from_seed(KeyType::ED25519, DEV_ACCOUNT_SEED);
         let pk = sk.public_key();
         let signer = InMemorySigner::from_secret_key(into_account_id.clone(), sk);
-        let block_ref = self.block_ref.unwrap_or_else(BlockReference::latest);
+
+        // Pin down the block, so that the account, code and state are all imported from it.
+        let block_ref = match self.block_ref.clone() {
+            Some(block_ref @ BlockReference::BlockId(_)) => block_ref,
+            block_ref => {
+                let block = self.from_network.client().view_block(block_ref).await?;
+                BlockId::Hash(block.header.hash).into()
+            }
+        };
 
         let mut account_view = self
             .from_network
@@ -131,23 +237,306 @@ impl<'a> ImportContractTransaction<'a> {
             patch = patch.code(&code);
         }
 
+        // The state is patched first and the account last, so that a failure part way
+        // through importing the state doesn't leave behind an account to call. The state
+        // records patched before the failure are not removed, though.
         if self.import_data {
-            let states = self
-                .from_network
-                .view_state(from_account_id)
+            let pages = StatePageStream::new(self.from_network.clone(), from_account_id.clone())
                 .block_reference(block_ref)
-                .await?;
-
-            patch = patch.states(
-                states
-                    .iter()
-                    .map(|(key, value)| (key.as_slice(), value.as_slice())),
-            );
+                .page_size(self.state_page_size);
+            self.import_state(pages, into_account_id, on_page).await?;
         }
 
         patch.transact().await?;
+
         Ok(Contract::new(signer, self.into_network.coerce()))
     }
+
+    /// Patch the state yielded by `pages` into sandbox, in requests of at most
+    /// `patch_batch_size` bytes.
+    async fn import_state(
+        &self,
+        mut pages: StatePageStream,
+        account_id: &AccountId,
+        mut on_page: impl FnMut(&StatePage) + Send,
+    ) -> Result<()> {
+        let mut batches = RecordBatches::new(&self.into_network, self.patch_batch_size);
+        let mut retries = 0;
+        loop {
+            let page = match pages.next().await {
+                Some(Ok(page)) => {
+                    retries = 0;
+                    page
+                }
+                // The stream resumes from the last key it yielded, so no state is lost or
+                // viewed twice by retrying.
+                Some(Err(err)) => {
+                    if retries >= MAX_STATE_PAGE_RETRIES {
+                        return Err(err);
+                    }
+                    tracing::warn!(
+                        target: "workspaces",
+                        "Failed to view state of {}, retrying: {}",
+                        self.account_id,
+                        err
+                    );
+                    tokio::time::sleep(Duration::from_secs(1 << retries)).await;
+                    retries += 1;
+                    continue;
+                }
+                None => break,
+            };
+
+            on_page(&page);
+            for (key, value) in page {
+                batches
+                    .push(StateRecord::Data {
+                        account_id: account_id.clone(),
+                        data_key: key.into(),
+                        value: value.into(),
+                    })
+                    .await?;
+            }
+        }
+
+        batches.finish().await?;
+        Ok(())
+    }
+}
+
+/// A [`Transaction`]-like object with details about importing a set of contracts, along
+/// with the accounts they depend on, from a network into sandbox local network. This object
+/// is usually given from calling into [`Worker::import_contracts`].
+///
+/// Every account is imported at the same block, and only once even if referenced multiple
+/// times. All imported accounts are given the same dev access key that [`import_contract`]
+/// gives, so the returned [`Contract`]s can sign transactions in sandbox.
+///
+/// [`Transaction`]: crate::operations::Transaction
+/// [`Worker::import_contracts`]: crate::Worker::import_contracts
+/// [`import_contract`]: crate::Worker::import_contract
+pub struct ImportContractsTransaction {
+    roots: Vec<AccountId>,
+    dependencies: Vec<AccountId>,
+    from_network: Worker<dyn Network>,
+    into_network: Worker<Sandbox>,
+    block_ref: Option<BlockReference>,
+    import_data: bool,
+    scan_state: bool,
+    max_depth: usize,
+    max_discovered: usize,
+}
+
+impl ImportContractsTransaction {
+    pub(crate) fn new(
+        roots: Vec<AccountId>,
+        from_network: Worker<dyn Network>,
+        into_network: Worker<Sandbox>,
+    ) -> Self {
+        Self {
+            roots,
+            dependencies: Vec::new(),
+            from_network,
+            into_network,
+            block_ref: None,
+            import_data: false,
+            scan_state: false,
+            max_depth: DEFAULT_MAX_DISCOVERY_DEPTH,
+            max_discovered: DEFAULT_MAX_DISCOVERED_ACCOUNTS,
+        }
+    }
+
+    /// Specify at which block height to import the contracts from. Be aware that only
+    /// archival networks will have the full history while networks like mainnet or testnet
+    /// only has the history from 5 or less epochs ago.
+    pub fn block_height(mut self, block_height: BlockHeight) -> Self {
+        self.block_ref = Some(BlockId::Height(block_height).into());
+        self
+    }
+
+    /// Specify at which block hash to import the contracts from. Be aware that only
+    /// archival networks will have the full history while networks like mainnet or testnet
+    /// only has the history from 5 or less epochs ago.
+    pub fn block_hash(mut self, block_hash: CryptoHash) -> Self {
+        self.block_ref =
+            Some(BlockId::Hash(near_primitives::hash::CryptoHash(block_hash.0)).into());
+        self
+    }
+
+    /// Also import the given accounts, such as the token contracts, oracles or
+    /// sub-accounts a root contract is known to depend on.
+    pub fn dependencies(mut self, account_ids: impl IntoIterator<Item = AccountId>) -> Self {
+        self.dependencies.extend(account_ids);
+        self
+    }
+
+    /// Import the state of every contract along with its code. See
+    /// [`ImportContractTransaction::with_data`] for more details.
+    pub fn with_data(mut self) -> Self {
+        self.import_data = true;
+        self
+    }
+
+    /// Discover more accounts to import by scanning the state of the imported contracts
+    /// for stored account IDs, such as the account ID of a token contract saved into the
+    /// state of an exchange. Only discovered accounts that have a contract deployed to them
+    /// are imported, which are then scanned as well, up to [`max_depth`] levels deep.
+    ///
+    /// [`max_depth`]: ImportContractsTransaction::max_depth
+    pub fn scan_state(mut self) -> Self {
+        self.scan_state = true;
+        self
+    }
+
+    /// Specify how many levels deep accounts discovered by [`scan_state`] are followed,
+    /// where the roots and dependencies are at level zero. Defaults to 2.
+    ///
+    /// [`scan_state`]: ImportContractsTransaction::scan_state
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Specify the maximum number of accounts discovered by [`scan_state`] to be imported.
+    /// Further discovered accounts are skipped. Defaults to 50.
+    ///
+    /// [`scan_state`]: ImportContractsTransaction::scan_state
+    pub fn max_discovered(mut self, max_discovered: usize) -> Self {
+        self.max_discovered = max_discovered;
+        self
+    }
+
+    /// Process the transaction, and return the imported contracts, starting with the
+    /// roots and dependencies in the order they were given.
+    pub async fn transact(self) -> Result<Vec<Contract>> {
+        // Pin down the block, so that all the accounts are imported from the same block.
+        let block = self
+            .from_network
+            .client()
+            .view_block(self.block_ref.clone())
+            .await?;
+        let block_ref: BlockReference = BlockId::Hash(block.header.hash).into();
+
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        for account_id in self.roots.iter().chain(&self.dependencies) {
+            if visited.insert(account_id.clone()) {
+                queue.push_back((account_id.clone(), 0));
+            }
+        }
+
+        let mut discovered = 0;
+        let mut contracts = Vec::new();
+        while let Some((account_id, depth)) = queue.pop_front() {
+            let scan = self.scan_state && depth < self.max_depth;
+            let mut found = BTreeSet::new();
+
+            let mut import = ImportContractTransaction::new(
+                &account_id,
+                self.from_network.clone(),
+                self.into_network.clone(),
+            )
+            .block_reference(block_ref.clone());
+            if self.import_data {
+                import = import.with_data();
+            }
+            let contract = import
+                .transact_with(|page| {
+                    if scan {
+                        scan_account_ids(page, &mut found);
+                    }
+                })
+                .await?;
+            contracts.push(contract);
+
+            if scan && !self.import_data {
+                let mut pages = StatePageStream::new(self.from_network.clone(), account_id)
+                    .block_reference(block_ref.clone());
+                while let Some(page) = pages.next().await {
+                    scan_account_ids(&page?, &mut found);
+                }
+            }
+
+            for account_id in found {
+                if discovered >= self.max_discovered {
+                    tracing::warn!(
+                        target: "workspaces",
+                        "Skipping import of {account_id}, since max_discovered={} accounts were already discovered",
+                        self.max_discovered
+                    );
+                    break;
+                }
+                if visited.contains(&account_id)
+                    || !has_contract(&self.from_network, &account_id, &block_ref).await?
+                {
+                    continue;
+                }
+
+                visited.insert(account_id.clone());
+                queue.push_back((account_id, depth + 1));
+                discovered += 1;
+            }
+        }
+
+        Ok(contracts)
+    }
+}
+
+/// Collect the account IDs that look to be stored within the keys and values of `page`.
+/// This is a heuristic over printable runs of bytes, so only named accounts with a dot in
+/// them and implicit accounts are considered, to avoid picking up any random word.
+fn scan_account_ids(page: &StatePage, found: &mut BTreeSet<AccountId>) {
+    let is_account_char =
+        |b: &u8| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-_.".contains(b);
+
+    for bytes in page.iter().flat_map(|(key, value)| [key, value]) {
+        for run in bytes.split(|b| !is_account_char(b)) {
+            if run.len() < 2 || run.len() > 64 {
+                continue;
+            }
+            let Ok(account_id) = std::str::from_utf8(run)
+                .unwrap_or_default()
+                .parse::<AccountId>()
+            else {
+                continue;
+            };
+
+            let id = account_id.as_str();
+            let is_implicit = id.len() == 64 || (id.len() == 42 && id.starts_with("0x"));
+            if id.contains('.') || is_implicit {
+                found.insert(account_id);
+            }
+        }
+    }
+}
+
+/// Check whether `account_id` exists and has a contract deployed to it at `block_ref`.
+async fn has_contract(
+    worker: &Worker<dyn Network>,
+    account_id: &AccountId,
+    block_ref: &BlockReference,
+) -> Result<bool> {
+    let result = worker
+        .client()
+        .query_cached(methods::query::RpcQueryRequest {
+            block_reference: block_ref.clone(),
+            request: QueryRequest::ViewAccount {
+                account_id: account_id.clone(),
+            },
+        })
+        .await;
+
+    match result {
+        Ok(RpcQueryResponse {
+            kind: QueryResponseKind::ViewAccount(account),
+            ..
+        }) => Ok(ContractState::from_account_view(&account) != ContractState::None),
+        Ok(_) => Err(RpcErrorCode::QueryReturnedInvalidData.message("while querying account")),
+        Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcQueryError::UnknownAccount { .. },
+        ))) => Ok(false),
+        Err(err) => Err(RpcErrorCode::QueryFailure.custom(err)),
+    }
 }
 
 /// Internal enum for determining whether to update the account on chain
@@ -157,12 +546,32 @@ enum AccountUpdate {
     FromCurrent(Box<dyn Fn(AccountDetails) -> AccountDetailsPatch + Send>),
 }
 
+/// Deletions to be made before patching the rest of the records of a [`PatchTransaction`].
+#[derive(Default)]
+struct Deletions {
+    keys: Vec<Vec<u8>>,
+    prefixes: Vec<Vec<u8>>,
+    access_keys: Vec<PublicKey>,
+    account: bool,
+}
+
+impl Deletions {
+    fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+            && self.prefixes.is_empty()
+            && self.access_keys.is_empty()
+            && !self.account
+    }
+}
+
 pub struct PatchTransaction {
     account_id: AccountId,
     records: Vec<StateRecord>,
     worker: Worker<Sandbox>,
     account_updates: Vec<AccountUpdate>,
     contract_state_update: Option<ContractState>,
+    deletions: Deletions,
+    error: Option<Error>,
 }
 
 impl PatchTransaction {
@@ -173,6 +582,8 @@ impl PatchTransaction {
             worker: worker.clone(),
             account_updates: vec![],
             contract_state_update: None,
+            deletions: Deletions::default(),
+            error: None,
         }
     }
 
@@ -258,6 +669,24 @@ impl PatchTransaction {
         self
     }
 
+    /// Similar to [`PatchTransaction::state`], but the value is borsh serialized, the same way
+    /// a contract using `near-sdk` stores its state. Combined with the storage keys computed by
+    /// [`collections`], this allows patching single entries of a collection, such as
+    /// `balances[alice] = 100`.
+    ///
+    /// [`collections`]: crate::types::collections
+    pub fn state_borsh<T: BorshSerialize + ?Sized>(mut self, key: &[u8], value: &T) -> Self {
+        match borsh::to_vec(value) {
+            Ok(value) => self.records.push(StateRecord::Data {
+                account_id: self.account_id.clone(),
+                data_key: key.to_vec().into(),
+                value: value.into(),
+            }),
+            Err(e) => self.error = Some(ErrorKind::DataConversion.custom(e)),
+        }
+        self
+    }
+
     /// Patch a series of states into the sandbox network. Similar to [`PatchTransaction::state`],
     /// but allows us to specify multiple state patches at once.
     pub fn states<'b, 'c, I>(mut self, states: I) -> Self
@@ -279,21 +708,86 @@ impl PatchTransaction {
         self
     }
 
+    /// Remove a key from the contract state of the account.
+    ///
+    /// Storage keys are removed by a contract bundled with the `bundled-contracts` feature,
+    /// patched in place of the code of the account for the length of the deletions. The code
+    /// is restored by the same patch request applying the other patches of this transaction.
+    /// See [`PatchTransaction::delete_access_key`] for how deletions are made.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn delete_state(mut self, key: &[u8]) -> Self {
+        self.deletions.keys.push(key.to_vec());
+        self
+    }
+
+    /// Remove every key starting with `prefix` from the contract state of the account. An
+    /// empty prefix wipes the entire contract state. See [`PatchTransaction::delete_state`]
+    /// for how storage keys are removed.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn delete_state_prefix(mut self, prefix: &[u8]) -> Self {
+        self.deletions.prefixes.push(prefix.to_vec());
+        self
+    }
+
+    /// Remove an access key from the account.
+    ///
+    /// The sandbox patch mechanism can only add or overwrite records, so deletions are made
+    /// by transactions signed with a full access key patched into the account for their
+    /// length, which works whether or not we hold any of its keys. The balance spent on gas is
+    /// restored by the same patch request applying the other patches of this transaction, so
+    /// the account looks as if only the key was removed. If one of the transactions fails,
+    /// the temporary key is removed and the code and balance restored before returning the
+    /// error, but deletions made by earlier transactions are kept. The deletions are made
+    /// first, and are not undone if the rest of the transaction fails.
+    pub fn delete_access_key(mut self, pk: PublicKey) -> Self {
+        self.deletions.access_keys.push(pk);
+        self
+    }
+
+    /// Remove the contract deployed to the account, along with any code patched in by
+    /// [`PatchTransaction::code`]. The contract state is left as is.
+    pub fn clear_code(mut self) -> Self {
+        self.contract_state_update = Some(ContractState::None);
+        self.records
+            .retain(|record| !matches!(record, StateRecord::Contract { .. }));
+        self
+    }
+
+    /// Delete the account along with all of its access keys, code and contract state. Its
+    /// balance is given to the root account of sandbox. Any other patches of this transaction
+    /// are applied after the account is deleted, so they can be used to recreate it.
+    pub fn delete_account(mut self) -> Self {
+        self.deletions.account = true;
+        self
+    }
+
     /// Perform the state patch transaction into the sandbox network.
     pub async fn transact(mut self) -> Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let restore = if !self.deletions.is_empty() {
+            self.delete().await?
+        } else {
+            None
+        };
+        // Details of the account as they will be once restored from making the deletions.
+        let current = async || match &restore {
+            Some(restore) => Ok(restore.account.clone()),
+            None => self.worker.view_account(&self.account_id).await,
+        };
+
         // NOTE: updating the account is done here because we need to fetch the current
         // account details from the chain. This is an async operation so it is deferred
         // till the transact function.
+        let code_patched = self.contract_state_update.is_some();
         let account_patch = if !self.account_updates.is_empty() {
             let mut account = AccountDetailsPatch::default();
             for update in self.account_updates {
                 // reduce the updates into a single account details patch
                 account.reduce(match update {
                     AccountUpdate::Update(account) => account,
-                    AccountUpdate::FromCurrent(f) => {
-                        let account = self.worker.view_account(&self.account_id).await?;
-                        f(account)
-                    }
+                    AccountUpdate::FromCurrent(f) => f(current().await?),
                 });
             }
 
@@ -302,44 +796,496 @@ impl PatchTransaction {
                 account.contract_state = Some(contract_state_update);
             }
 
-            Some(account)
+            Some(account.into())
         } else if let Some(contract_state_update) = self.contract_state_update {
             // No account patch, but we have a code patch. We need to fetch the current account
             // to reflect the code hash change.
-            let mut account = self.worker.view_account(&self.account_id).await?;
+            let mut account = current().await?;
             account.contract_state = contract_state_update;
-            Some(account.into())
+            Some(account)
         } else {
-            None
+            restore.as_ref().map(|restore| restore.account.clone())
         };
 
         // Account patch should be the first entry in the records, since the account might not
-        // exist yet and the consequent patches might lookup the account on the chain.
-        let records = if let Some(account) = account_patch {
-            let account: AccountDetails = account.into();
-            let mut records = vec![StateRecord::Account {
+        // exist yet and the consequent patches might lookup the account on the chain. The code
+        // replaced to make the deletions is restored in the same request, unless patched over.
+        let mut records = vec![];
+        if let Some(account) = account_patch {
+            records.push(StateRecord::Account {
                 account_id: self.account_id.clone(),
                 account: account.into_near_account(),
-            }];
-            records.extend(self.records);
-            records
+            });
+        }
+        if let Some(code) = restore.and_then(|restore| restore.code)
+            && !code_patched
+        {
+            records.push(StateRecord::Contract {
+                account_id: self.account_id.clone(),
+                code,
+            });
+        }
+        records.extend(self.records);
+
+        patch_records(&self.worker, records).await
+    }
+}
+
+/// State of an account replaced to make the deletions of a [`PatchTransaction`], restored by
+/// the patch request applying the rest of the transaction.
+struct Restore {
+    /// Details of the account once deleted from, with its balance and code hash from before.
+    account: AccountDetails,
+    /// Code of the account, if it was replaced by [`STATE_CLEANER_WASM`].
+    code: Option<Vec<u8>>,
+}
+
+impl PatchTransaction {
+    /// Make the deletions of this transaction, by patching in a temporary full access key
+    /// to sign a transaction with, along with [`STATE_CLEANER_WASM`] if state needs to be
+    /// removed. Returns what needs to be restored afterwards, unless the account was deleted.
+    async fn delete(&mut self) -> Result<Option<Restore>> {
+        let deletions = std::mem::take(&mut self.deletions);
+        let before = self.worker.view_account(&self.account_id).await?;
+
+        let mut keys = deletions.keys;
+        for prefix in deletions.prefixes {
+            let mut pages = self
+                .worker
+                .view_state_pages(&self.account_id)
+                .prefix(&prefix);
+            while let Some(page) = pages.next().await {
+                keys.extend(page?.into_iter().map(|(key, _)| key));
+            }
+        }
+        let clean_state = !deletions.account && !keys.is_empty();
+        let code = match &before.contract_state {
+            ContractState::LocalHash(_) if clean_state => {
+                Some(self.worker.view_code(&self.account_id).await?)
+            }
+            _ => None,
+        };
+
+        // Build the transactions up front, so that nothing but the transactions themselves can
+        // fail once the account has been patched to make them.
+        let sk = SecretKey::from_random(KeyType::ED25519);
+        let signer = Account::from_secret_key(self.account_id.clone(), sk.clone(), &self.worker);
+        let txs = if deletions.account {
+            let beneficiary_id = self.worker.root_account()?.id().clone();
+            vec![
+                signer
+                    .batch(&self.account_id)
+                    .delete_account(&beneficiary_id),
+            ]
         } else {
-            self.records
+            // Each call to the state cleaner gets a transaction of its own, the last of which
+            // also removes the access keys, so that the deletions take as few blocks as
+            // possible.
+            let mut txs = vec![];
+            if clean_state {
+                for chunk in keys.chunks(MAX_STATE_DELETIONS_PER_CALL) {
+                    let args =
+                        borsh::to_vec(chunk).map_err(|e| ErrorKind::DataConversion.custom(e))?;
+                    txs.push(
+                        signer
+                            .batch(&self.account_id)
+                            .call(Function::new("clean").args(args).gas(STATE_DELETION_GAS)),
+                    );
+                }
+            }
+            let mut last = txs.pop().unwrap_or_else(|| signer.batch(&self.account_id));
+            for pk in deletions.access_keys {
+                last = last.delete_key(pk);
+            }
+            txs.push(last.delete_key(sk.public_key()));
+            txs
         };
 
-        self.worker
-            .client()
-            .query(&RpcSandboxPatchStateRequest {
-                records: records.clone(),
+        let mut account = before.clone();
+        if clean_state {
+            account.contract_state =
+                ContractState::LocalHash(CryptoHash::hash_bytes(STATE_CLEANER_WASM));
+        }
+        let mut records = vec![
+            StateRecord::Account {
+                account_id: self.account_id.clone(),
+                account: account.into_near_account(),
+            },
+            StateRecord::AccessKey {
+                account_id: self.account_id.clone(),
+                public_key: sk.public_key().0.into(),
+                access_key: AccessKey::full_access().into(),
+            },
+        ];
+        if clean_state {
+            records.push(StateRecord::Contract {
+                account_id: self.account_id.clone(),
+                code: STATE_CLEANER_WASM.to_vec(),
+            });
+        }
+        patch_records(&self.worker, records).await?;
+
+        for tx in txs {
+            let result = match tx.transact().await {
+                Ok(outcome) => outcome.into_result().map(drop).map_err(Error::from),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                self.undo_delete(&signer, &before, code).await;
+                return Err(err);
+            }
+        }
+        if deletions.account {
+            return Ok(None);
+        }
+
+        Ok(Some(Restore {
+            account: self.restored_account(&before).await?,
+            code,
+        }))
+    }
+
+    /// Undo patching in the temporary key and the state cleaner after a transaction making
+    /// the deletions failed, leaving the account with its original key set, balance and code.
+    /// Whatever was deleted before the failure stays deleted. Failing to undo is only logged,
+    /// so that the error of the deletions is returned instead.
+    async fn undo_delete(&self, signer: &Account, before: &AccountDetails, code: Option<Vec<u8>>) {
+        let result = signer
+            .batch(&self.account_id)
+            .delete_key(signer.secret_key().public_key())
+            .transact()
+            .await;
+        if let Err(err) = result.map(|outcome| outcome.into_result()) {
+            tracing::warn!(
+                target: "workspaces",
+                "Failed to remove the temporary access key of {}: {}",
+                self.account_id,
+                err
+            );
+        }
+
+        let account = match self.restored_account(before).await {
+            Ok(account) => account,
+            Err(_) => before.clone(),
+        };
+        let mut records = vec![StateRecord::Account {
+            account_id: self.account_id.clone(),
+            account: account.into_near_account(),
+        }];
+        if let Some(code) = code {
+            records.push(StateRecord::Contract {
+                account_id: self.account_id.clone(),
+                code,
+            });
+        }
+        if let Err(err) = patch_records(&self.worker, records).await {
+            tracing::warn!(
+                target: "workspaces",
+                "Failed to restore the code and balance of {}: {}",
+                self.account_id,
+                err
+            );
+        }
+    }
+
+    /// Current details of the account, with the balance spent on gas and the code replaced by
+    /// the state cleaner restored from `before`.
+    async fn restored_account(&self, before: &AccountDetails) -> Result<AccountDetails> {
+        let mut account = self.worker.view_account(&self.account_id).await?;
+        account.balance = before.balance;
+        account.contract_state = before.contract_state.clone();
+        Ok(account)
+    }
+}
+
+/// A [`Transaction`]-like object that patches the state of many accounts at once, usually
+/// given from calling into [`Worker::patch_many`]. Unlike [`PatchTransaction`], which patches
+/// a single account per request, every record is submitted in a single patch request with
+/// [`transact`], which fails if they would exceed the payload limit of the RPC. Records too
+/// large for a single request can instead be split into as few requests as possible with
+/// [`transact_in_batches`], at the cost of the patch no longer being atomic.
+///
+/// [`transact`]: PatchManyTransaction::transact
+/// [`transact_in_batches`]: PatchManyTransaction::transact_in_batches
+/// [`Transaction`]: crate::operations::Transaction
+/// [`Worker::patch_many`]: crate::Worker::patch_many
+pub struct PatchManyTransaction {
+    worker: Worker<Sandbox>,
+    accounts: Vec<(AccountId, AccountDetailsPatch)>,
+    code_updates: Vec<(AccountId, CryptoHash)>,
+    records: Vec<StateRecord>,
+    max_payload_size: usize,
+    error: Option<Error>,
+}
+
+impl PatchManyTransaction {
+    pub(crate) fn new(worker: &Worker<Sandbox>) -> Self {
+        Self {
+            worker: worker.clone(),
+            accounts: vec![],
+            code_updates: vec![],
+            records: vec![],
+            max_payload_size: DEFAULT_PATCH_PAYLOAD_SIZE,
+            error: None,
+        }
+    }
+
+    /// Patch and overwrite the details of `account_id`, creating the account if it does not
+    /// exist. Similar to [`PatchTransaction::account`].
+    pub fn account(mut self, account_id: &AccountId, account: AccountDetailsPatch) -> Self {
+        self.accounts.push((account_id.clone(), account));
+        self
+    }
+
+    /// Add or overwrite an access key of `account_id`.
+    pub fn access_key(mut self, account_id: &AccountId, pk: PublicKey, ak: AccessKey) -> Self {
+        self.records.push(StateRecord::AccessKey {
+            account_id: account_id.clone(),
+            // since nearcore 2.13 this field is a `PublicKeyHandle`
+            public_key: pk.0.into(),
+            access_key: ak.into(),
+        });
+        self
+    }
+
+    /// Set the code of `account_id`. The code hash of the account is updated to match, either
+    /// in the details given to [`PatchManyTransaction::account`] or in its current details.
+    pub fn code(mut self, account_id: &AccountId, wasm_bytes: &[u8]) -> Self {
+        self.code_updates
+            .push((account_id.clone(), CryptoHash::hash_bytes(wasm_bytes)));
+        self.records.push(StateRecord::Contract {
+            account_id: account_id.clone(),
+            code: wasm_bytes.to_vec(),
+        });
+        self
+    }
+
+    /// Patch a key of the contract state of `account_id`.
+    pub fn state(mut self, account_id: &AccountId, key: &[u8], value: &[u8]) -> Self {
+        self.records.push(StateRecord::Data {
+            account_id: account_id.clone(),
+            data_key: key.to_vec().into(),
+            value: value.to_vec().into(),
+        });
+        self
+    }
+
+    /// Patch a series of keys of the contract state of `account_id`.
+    pub fn states<'b, 'c, I>(mut self, account_id: &AccountId, states: I) -> Self
+    where
+        I: IntoIterator<Item = (&'b [u8], &'c [u8])>,
+    {
+        self.records
+            .extend(states.into_iter().map(|(key, value)| StateRecord::Data {
+                account_id: account_id.clone(),
+                data_key: key.to_vec().into(),
+                value: value.to_vec().into(),
+            }));
+        self
+    }
+
+    /// Patch a key of the contract state of `account_id` with a borsh serialized value.
+    /// Similar to [`PatchTransaction::state_borsh`].
+    pub fn state_borsh<T: BorshSerialize + ?Sized>(
+        mut self,
+        account_id: &AccountId,
+        key: &[u8],
+        value: &T,
+    ) -> Self {
+        match borsh::to_vec(value) {
+            Ok(value) => self.records.push(StateRecord::Data {
+                account_id: account_id.clone(),
+                data_key: key.to_vec().into(),
+                value: value.into(),
+            }),
+            Err(e) => self.error = Some(ErrorKind::DataConversion.custom(e)),
+        }
+        self
+    }
+
+    /// Maximum size in bytes of a single patch request, past which [`transact`] fails and
+    /// [`transact_in_batches`] splits the records into multiple requests. Defaults to 8 MiB,
+    /// which is within the default JSON payload limit of the RPC.
+    ///
+    /// [`transact`]: PatchManyTransaction::transact
+    /// [`transact_in_batches`]: PatchManyTransaction::transact_in_batches
+    pub fn max_payload_size(mut self, bytes: usize) -> Self {
+        self.max_payload_size = bytes;
+        self
+    }
+
+    /// Perform the patches in a single request, returning a [`PatchSummary`] of what was
+    /// applied. Either every patch is applied or none are, and an error is returned without
+    /// patching anything if the records do not fit within [`max_payload_size`].
+    ///
+    /// [`max_payload_size`]: PatchManyTransaction::max_payload_size
+    pub async fn transact(self) -> Result<PatchSummary> {
+        self.transact_with(false).await
+    }
+
+    /// Perform the patches, split into as few requests of at most [`max_payload_size`]
+    /// bytes as possible, returning a [`PatchSummary`] of what was applied. This is not
+    /// atomic: if a request fails, the records of the requests before it stay patched.
+    ///
+    /// [`max_payload_size`]: PatchManyTransaction::max_payload_size
+    pub async fn transact_in_batches(self) -> Result<PatchSummary> {
+        self.transact_with(true).await
+    }
+
+    async fn transact_with(self, split: bool) -> Result<PatchSummary> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        // The account records go first, since the account might not exist yet and the
+        // consequent patches might lookup the account on the chain.
+        let mut accounts: Vec<(AccountId, AccountDetailsPatch)> = Vec::new();
+        for (account_id, patch) in self.accounts {
+            match accounts.iter_mut().find(|(id, _)| *id == account_id) {
+                Some((_, account)) => account.reduce(patch),
+                None => accounts.push((account_id, patch)),
+            }
+        }
+        for (account_id, code_hash) in self.code_updates {
+            let contract_state = ContractState::LocalHash(code_hash);
+            match accounts.iter_mut().find(|(id, _)| *id == account_id) {
+                Some((_, account)) => account.contract_state = Some(contract_state),
+                None => {
+                    let mut account = self.worker.view_account(&account_id).await?;
+                    account.contract_state = contract_state;
+                    accounts.push((account_id, account.into()));
+                }
+            }
+        }
+
+        let mut summary = PatchSummary::default();
+        let records = accounts
+            .into_iter()
+            .map(|(account_id, account)| StateRecord::Account {
+                account_id,
+                account: AccountDetails::from(account).into_near_account(),
             })
-            .await
-            .map_err(|err| SandboxErrorCode::PatchStateFailure.custom(err))?;
+            .chain(self.records)
+            .collect::<Vec<_>>();
+        for record in &records {
+            if let StateRecord::Account { account_id, .. }
+            | StateRecord::AccessKey { account_id, .. }
+            | StateRecord::Contract { account_id, .. }
+            | StateRecord::Data { account_id, .. } = record
+                && !summary.accounts.contains(account_id)
+            {
+                summary.accounts.push(account_id.clone());
+            }
+        }
+        summary.records = records.len();
 
-        self.worker
-            .client()
-            .query(&RpcSandboxPatchStateRequest { records })
-            .await
-            .map_err(|err| SandboxErrorCode::PatchStateFailure.custom(err))?;
+        let mut batches = RecordBatches::new(&self.worker, self.max_payload_size);
+        for record in records {
+            if !split && batches.is_full_for(&record)? {
+                return Err(SandboxErrorCode::PatchStateFailure.message(format!(
+                    "records exceed max_payload_size={} bytes of a single patch request, use \
+                    transact_in_batches to split them into multiple requests",
+                    self.max_payload_size
+                )));
+            }
+            batches.push(record).await?;
+        }
+        summary.requests = batches.finish().await?;
+
+        Ok(summary)
+    }
+}
+
+/// What was applied by a [`PatchManyTransaction`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PatchSummary {
+    /// The accounts that were patched, in the order they were first patched.
+    pub accounts: Vec<AccountId>,
+    /// The number of records patched across all the accounts.
+    pub records: usize,
+    /// The number of patch requests the records were split into.
+    pub requests: usize,
+}
+
+/// Patches records into sandbox in order, in batches of at most `max_size` bytes of JSON.
+/// Every bulk patch goes through this, so that its requests stay within the payload limit of
+/// the RPC.
+pub(crate) struct RecordBatches<'a> {
+    worker: &'a Worker<Sandbox>,
+    max_size: usize,
+    batch: Vec<StateRecord>,
+    batch_size: usize,
+    requests: usize,
+}
+
+impl<'a> RecordBatches<'a> {
+    pub(crate) fn new(worker: &'a Worker<Sandbox>, max_size: usize) -> Self {
+        Self {
+            worker,
+            max_size,
+            batch: Vec::new(),
+            batch_size: 0,
+            requests: 0,
+        }
+    }
+
+    /// Whether adding `record` would patch the current batch first, for not fitting in it.
+    pub(crate) fn is_full_for(&self, record: &StateRecord) -> Result<bool> {
+        Ok(!self.batch.is_empty() && self.batch_size + record_size(record)? > self.max_size)
+    }
+
+    /// Add `record` to the current batch, patching the batch first if the record does not
+    /// fit in it. A record larger than `max_size` on its own is patched alone.
+    pub(crate) async fn push(&mut self, record: StateRecord) -> Result<()> {
+        let size = record_size(&record)?;
+        if !self.batch.is_empty() && self.batch_size + size > self.max_size {
+            self.flush().await?;
+        }
+        self.batch_size += size;
+        self.batch.push(record);
+        Ok(())
+    }
+
+    /// Patch the remaining records, returning the number of requests made in total.
+    pub(crate) async fn finish(mut self) -> Result<usize> {
+        if !self.batch.is_empty() {
+            self.flush().await?;
+        }
+        Ok(self.requests)
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        patch_records(self.worker, std::mem::take(&mut self.batch)).await?;
+        self.batch_size = 0;
+        self.requests += 1;
         Ok(())
     }
 }
+
+fn record_size(record: &StateRecord) -> Result<usize> {
+    serde_json::to_vec(record)
+        .map(|json| json.len())
+        .map_err(|e| ErrorKind::DataConversion.custom(e))
+}
+
+/// Send the records to be patched into the sandbox network.
+pub(crate) async fn patch_records(
+    worker: &Worker<Sandbox>,
+    records: Vec<StateRecord>,
+) -> Result<()> {
+    worker
+        .client()
+        .query(&RpcSandboxPatchStateRequest {
+            records: records.clone(),
+        })
+        .await
+        .map_err(|err| SandboxErrorCode::PatchStateFailure.custom(err))?;
+
+    worker
+        .client()
+        .query(&RpcSandboxPatchStateRequest { records })
+        .await
+        .map_err(|err| SandboxErrorCode::PatchStateFailure.custom(err))?;
+    Ok(())
+}
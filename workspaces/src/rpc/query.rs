@@ -34,6 +34,9 @@
 
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
+use std::num::NonZeroU32;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
 
 use near_account_id::AccountId;
 use near_jsonrpc_client::methods::query::RpcQueryResponse;
@@ -41,17 +44,27 @@ use near_jsonrpc_client::methods::{self, RpcMethod};
 use near_jsonrpc_primitives::types::chunks::ChunkReference;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_primitives::types::{BlockId, BlockReference, StoreKey};
-use near_primitives::views::{BlockView, QueryRequest};
+use near_primitives::views::{
+    BlockView, QueryRequest, StateChangeKindView, StateChangesRequestView,
+};
 use near_token::NearToken;
+use serde::Serialize;
 
-use crate::error::RpcErrorCode;
+use crate::error::{ErrorKind, RpcErrorCode};
 use crate::operations::Function;
 use crate::result::ViewResultDetails;
 use crate::rpc::client::Client;
+use crate::rpc::stream::{StateChangesStream, StatePageStream};
 use crate::rpc::{BoxFuture, tool};
 use crate::types::account::AccountDetails;
-use crate::types::{AccessKey, AccessKeyInfo, BlockHeight, Finality, PublicKey, ShardId};
-use crate::{Block, Chunk, CryptoHash, Result};
+use crate::types::{
+    AccessKey, AccessKeyInfo, BlockHeight, Finality, PublicKey, ShardId, StateChange, StateChanges,
+    StateDiff, ValueChange,
+};
+use crate::{Block, Chunk, CryptoHash, Network, Result, Worker};
+
+/// Number of storage keys to look up at the same time when computing a [`StateDiff`].
+const STATE_DIFF_CONCURRENCY: usize = 8;
 
 /// `Query` object allows creating queries into the network of our choice. This object is
 /// usually given from making calls from other functions such as [`view_state`].
@@ -110,7 +123,7 @@ impl<'a, T, R> std::future::IntoFuture for Query<'a, T>
 where
     T: ProcessQuery<Output = R> + Send + Sync + 'static,
     <T as ProcessQuery>::Method: RpcMethod + Debug + Send + Sync,
-    <<T as ProcessQuery>::Method as RpcMethod>::Response: Debug + Send + Sync,
+    <<T as ProcessQuery>::Method as RpcMethod>::Response: Debug + Serialize + Send + Sync,
     <<T as ProcessQuery>::Method as RpcMethod>::Error: Debug + Display + Send + Sync,
 {
     type Output = Result<R>;
@@ -124,7 +137,7 @@ where
             let block_reference = self.block_ref.unwrap_or_else(BlockReference::latest);
             let resp = self
                 .client
-                .query(self.method.into_request(block_reference)?)
+                .query_cached(self.method.into_request(block_reference)?)
                 .await
                 .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
 
@@ -174,6 +187,8 @@ pub struct ViewBlock;
 pub struct ViewState {
     account_id: AccountId,
     prefix: Option<Vec<u8>>,
+    after_key: Option<Vec<u8>>,
+    limit: Option<NonZeroU32>,
 }
 
 pub struct ViewAccessKey {
@@ -301,8 +316,8 @@ impl ProcessQuery for ViewState {
             request: QueryRequest::ViewState {
                 account_id: self.account_id,
                 prefix: StoreKey::from(self.prefix.unwrap_or_default()),
-                after_key: None,
-                limit: None,
+                after_key: self.after_key.map(StoreKey::from),
+                limit: self.limit,
                 include_proof: false,
             },
         })
@@ -323,6 +338,8 @@ impl<'a> Query<'a, ViewState> {
             ViewState {
                 account_id: id.clone(),
                 prefix: None,
+                after_key: None,
+                limit: None,
             },
         )
     }
@@ -332,6 +349,21 @@ impl<'a> Query<'a, ViewState> {
         self.method.prefix = Some(value.into());
         self
     }
+
+    /// Only view the keys that come strictly after `key` in lexicographic order. Along with
+    /// [`limit`], this allows viewing a large state a page at a time.
+    ///
+    /// [`limit`]: Query::limit
+    pub fn after_key(mut self, key: &[u8]) -> Self {
+        self.method.after_key = Some(key.into());
+        self
+    }
+
+    /// Set the maximum number of keys to view. A limit of zero is treated as no limit.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.method.limit = NonZeroU32::new(limit);
+        self
+    }
 }
 
 impl ProcessQuery for ViewAccessKey {
@@ -371,11 +403,9 @@ impl ProcessQuery for ViewAccessKeyList {
 
     fn from_response(resp: <Self::Method as RpcMethod>::Response) -> Result<Self::Output> {
         match resp.kind {
-            QueryResponseKind::AccessKeyList(keylist) => keylist
-                .keys
-                .into_iter()
-                .map(AccessKeyInfo::try_from)
-                .collect(),
+            QueryResponseKind::AccessKeyList(keylist) => {
+                Ok(keylist.keys.into_iter().map(AccessKeyInfo::from).collect())
+            }
             _ => Err(RpcErrorCode::QueryReturnedInvalidData.message("while querying access keys")),
         }
     }
@@ -485,3 +515,387 @@ impl<'a> std::future::IntoFuture for QueryChunk<'a> {
         })
     }
 }
+
+/// Query object to query for the typed [`StateChange`]s that happened within a block, given
+/// from calling into [`Worker::state_changes`]. The default behavior where a block is not
+/// specified will query the latest block.
+///
+/// By default, changes to all accounts touched within the block are returned. These can be
+/// narrowed down to specific accounts with [`account`] or [`accounts`], and to contract
+/// storage keys starting with a specific prefix with [`key_prefix`].
+///
+/// [`StateChange`]: crate::types::StateChange
+/// [`Worker::state_changes`]: crate::Worker::state_changes
+/// [`account`]: QueryStateChanges::account
+/// [`accounts`]: QueryStateChanges::accounts
+/// [`key_prefix`]: QueryStateChanges::key_prefix
+pub struct QueryStateChanges<'a> {
+    client: &'a Client,
+    block_ref: Option<BlockReference>,
+    filter: StateChangesFilter,
+}
+
+impl<'a> QueryStateChanges<'a> {
+    pub(crate) fn new(client: &'a Client) -> Self {
+        Self {
+            client,
+            block_ref: None,
+            filter: StateChangesFilter::default(),
+        }
+    }
+
+    /// Specify at which block height to query the changes from. Note that only archival
+    /// networks will have the full history while networks like mainnet or testnet will
+    /// only have the history from 5 or less epochs ago.
+    pub fn block_height(mut self, height: BlockHeight) -> Self {
+        self.block_ref = Some(BlockId::Height(height).into());
+        self
+    }
+
+    /// Specify at which block hash to query the changes from. Note that only archival
+    /// networks will have the full history while networks like mainnet or testnet will
+    /// only have the history from 5 or less epochs ago.
+    pub fn block_hash(mut self, hash: CryptoHash) -> Self {
+        self.block_ref = Some(BlockId::Hash(near_primitives::hash::CryptoHash(hash.0)).into());
+        self
+    }
+
+    /// Specify at which block [`Finality`] to query the changes from.
+    pub fn finality(mut self, value: Finality) -> Self {
+        self.block_ref = Some(value.into());
+        self
+    }
+
+    /// Only return changes made to the state of `account_id`. Can be called multiple times
+    /// to include more accounts.
+    pub fn account(mut self, account_id: &AccountId) -> Self {
+        self.filter.add_account(account_id.clone());
+        self
+    }
+
+    /// Only return changes made to the state of the given accounts.
+    pub fn accounts(mut self, account_ids: impl IntoIterator<Item = AccountId>) -> Self {
+        for account_id in account_ids {
+            self.filter.add_account(account_id);
+        }
+        self
+    }
+
+    /// Only return changes made to contract storage keys starting with `prefix`. Note that
+    /// this excludes all other kinds of changes, such as account or access key changes.
+    pub fn key_prefix(mut self, prefix: impl AsRef<[u8]>) -> Self {
+        self.filter.key_prefix = Some(prefix.as_ref().to_vec());
+        self
+    }
+}
+
+impl<'a> std::future::IntoFuture for QueryStateChanges<'a> {
+    type Output = Result<StateChanges>;
+    type IntoFuture = BoxFuture<'a, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            // Resolve the block first, so that all the following requests are made against the
+            // same block even when a finality is supplied.
+            let block: Block = self.client.view_block(self.block_ref).await?.into();
+            self.filter
+                .state_changes_in_block(self.client, &block)
+                .await
+        })
+    }
+}
+
+/// Query object to compute the [`StateDiff`] of a contract's storage between two blocks,
+/// given from calling into [`Contract::state_diff`].
+///
+/// By default, the state is viewed a page at a time at both blocks and the two snapshots are
+/// compared, which takes a number of requests proportional to the size of the state. For
+/// large states changing over a few blocks, [`walk_blocks`] instead collects the keys changed
+/// in every block in between, and only looks up their values at the starting block. Note
+/// that only archival networks will have the full history while networks like mainnet or
+/// testnet will only have the history from 5 or less epochs ago.
+///
+/// [`Contract::state_diff`]: crate::Contract::state_diff
+/// [`walk_blocks`]: QueryStateDiff::walk_blocks
+pub struct QueryStateDiff<'a> {
+    worker: &'a Worker<dyn Network>,
+    account_id: AccountId,
+    from: BlockHeight,
+    to: BlockHeight,
+    prefix: Option<Vec<u8>>,
+    walk_blocks: bool,
+}
+
+impl<'a> QueryStateDiff<'a> {
+    pub(crate) fn new(
+        worker: &'a Worker<dyn Network>,
+        account_id: &AccountId,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Self {
+        Self {
+            worker,
+            account_id: account_id.clone(),
+            from,
+            to,
+            prefix: None,
+            walk_blocks: false,
+        }
+    }
+
+    /// Only diff the storage keys starting with `value`.
+    pub fn prefix(mut self, value: &[u8]) -> Self {
+        self.prefix = Some(value.into());
+        self
+    }
+
+    /// Compute the diff from the state changes of every block in between, rather than from
+    /// snapshots of the state at both blocks. This makes one request per block in the range,
+    /// plus one per changed key, so it is only worth it for large states diffed over a short
+    /// range of blocks.
+    pub fn walk_blocks(mut self) -> Self {
+        self.walk_blocks = true;
+        self
+    }
+
+    /// Diff the state viewed a page at a time at both blocks.
+    async fn diff_snapshots(&self) -> Result<StateDiff> {
+        let pages = |height| {
+            StatePageStream::new(self.worker.clone(), self.account_id.clone())
+                .prefix(self.prefix.as_deref().unwrap_or_default())
+                .block_height(height)
+        };
+
+        let mut old = HashMap::new();
+        let mut from = pages(self.from);
+        while let Some(page) = from.next().await {
+            old.extend(page?);
+        }
+
+        let mut diff = StateDiff::default();
+        let mut to = pages(self.to);
+        while let Some(page) = to.next().await {
+            for (key, new) in page? {
+                match old.remove(&key) {
+                    None => {
+                        diff.added.insert(key, new);
+                    }
+                    Some(old) if old != new => {
+                        diff.modified.insert(key, ValueChange { old, new });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        diff.removed = old;
+
+        Ok(diff)
+    }
+
+    /// Diff the state by walking the state changes of every block in between.
+    async fn diff_blocks(&self) -> Result<StateDiff> {
+        // The latest value of every key changed after the starting block, where `None`
+        // means the key was deleted.
+        let mut latest = HashMap::new();
+        let mut changes = StateChangesStream::new(self.worker.clone(), self.from + 1..self.to + 1)
+            .account(&self.account_id)
+            .key_prefix(self.prefix.as_deref().unwrap_or_default());
+        while let Some(block_changes) = changes.next().await {
+            for change in block_changes?.changes {
+                match change {
+                    StateChange::DataUpdate { key, value, .. } => {
+                        latest.insert(key, Some(value));
+                    }
+                    StateChange::DataDeletion { key, .. } => {
+                        latest.insert(key, None);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let latest = latest.into_iter().collect::<Vec<_>>();
+        let keys = latest
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        let olds = stream::iter(keys)
+            .map(|key| self.value_at_start(key))
+            .buffered(STATE_DIFF_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut diff = StateDiff::default();
+        for ((key, new), old) in latest.into_iter().zip(olds) {
+            match (old, new) {
+                (None, Some(new)) => {
+                    diff.added.insert(key, new);
+                }
+                (Some(old), None) => {
+                    diff.removed.insert(key, old);
+                }
+                (Some(old), Some(new)) if old != new => {
+                    diff.modified.insert(key, ValueChange { old, new });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// Fetch the value of `key` at the starting block, if the key existed back then.
+    async fn value_at_start(&self, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let resp = self
+            .worker
+            .client()
+            .query_cached(methods::query::RpcQueryRequest {
+                block_reference: BlockId::Height(self.from).into(),
+                request: QueryRequest::ViewState {
+                    account_id: self.account_id.clone(),
+                    prefix: StoreKey::from(key.clone()),
+                    after_key: None,
+                    // Keys are ordered, so the key itself comes first if it exists.
+                    limit: NonZeroU32::new(1),
+                    include_proof: false,
+                },
+            })
+            .await
+            .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
+
+        match resp.kind {
+            QueryResponseKind::ViewState(state) => Ok(state
+                .values
+                .into_iter()
+                .find(|item| *item.key == key)
+                .map(|item| item.value.into())),
+            _ => Err(RpcErrorCode::QueryReturnedInvalidData.message("while querying state")),
+        }
+    }
+}
+
+impl<'a> std::future::IntoFuture for QueryStateDiff<'a> {
+    type Output = Result<StateDiff>;
+    type IntoFuture = BoxFuture<'a, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            if self.from > self.to {
+                return Err(ErrorKind::Other.message(format!(
+                    "cannot diff state from block {} to an earlier block {}",
+                    self.from, self.to
+                )));
+            }
+
+            if self.walk_blocks {
+                self.diff_blocks().await
+            } else {
+                self.diff_snapshots().await
+            }
+        })
+    }
+}
+
+/// Filters on which state changes to fetch, shared between [`QueryStateChanges`] and
+/// the stream of state changes over a range of blocks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateChangesFilter {
+    /// Accounts to fetch changes for. `None` means every account touched within the block.
+    pub(crate) account_ids: Option<Vec<AccountId>>,
+    pub(crate) key_prefix: Option<Vec<u8>>,
+}
+
+impl StateChangesFilter {
+    pub(crate) fn add_account(&mut self, account_id: AccountId) {
+        let account_ids = self.account_ids.get_or_insert_with(Vec::new);
+        if !account_ids.contains(&account_id) {
+            account_ids.push(account_id);
+        }
+    }
+
+    fn includes(&self, account_id: &AccountId) -> bool {
+        self.account_ids
+            .as_ref()
+            .is_none_or(|account_ids| account_ids.contains(account_id))
+    }
+
+    /// Fetch all the changes within `block` that pass this filter. The RPC only provides
+    /// changes per kind of change and per account, so the touched accounts are looked up first
+    /// and only the kinds of changes that actually happened to them are requested.
+    pub(crate) async fn state_changes_in_block(
+        &self,
+        client: &Client,
+        block: &Block,
+    ) -> Result<StateChanges> {
+        let block_reference: BlockReference =
+            BlockId::Hash(near_primitives::hash::CryptoHash(block.hash().0)).into();
+        let touched = client.changes_in_block(block_reference.clone()).await?;
+
+        let mut accounts = Vec::new();
+        let mut access_keys = Vec::new();
+        let mut data = Vec::new();
+        let mut code = Vec::new();
+        for kind in touched.changes {
+            let (account_ids, account_id) = match kind {
+                StateChangeKindView::AccountTouched { account_id } => (&mut accounts, account_id),
+                StateChangeKindView::AccessKeyTouched { account_id } => {
+                    (&mut access_keys, account_id)
+                }
+                StateChangeKindView::DataTouched { account_id } => (&mut data, account_id),
+                StateChangeKindView::ContractCodeTouched { account_id } => (&mut code, account_id),
+            };
+            if self.includes(&account_id) && !account_ids.contains(&account_id) {
+                account_ids.push(account_id);
+            }
+        }
+
+        let mut requests = Vec::new();
+        if let Some(prefix) = &self.key_prefix {
+            requests.push(StateChangesRequestView::DataChanges {
+                account_ids: data,
+                key_prefix: StoreKey::from(prefix.clone()),
+            });
+        } else {
+            requests.push(StateChangesRequestView::AccountChanges {
+                account_ids: accounts,
+            });
+            requests.push(StateChangesRequestView::AllAccessKeyChanges {
+                account_ids: access_keys,
+            });
+            requests.push(StateChangesRequestView::ContractCodeChanges { account_ids: code });
+            requests.push(StateChangesRequestView::DataChanges {
+                account_ids: data,
+                key_prefix: StoreKey::from(Vec::new()),
+            });
+        }
+
+        let mut changes = Vec::new();
+        for request in requests {
+            let is_empty = match &request {
+                StateChangesRequestView::AccountChanges { account_ids }
+                | StateChangesRequestView::AllAccessKeyChanges { account_ids }
+                | StateChangesRequestView::ContractCodeChanges { account_ids }
+                | StateChangesRequestView::DataChanges { account_ids, .. } => {
+                    account_ids.is_empty()
+                }
+                StateChangesRequestView::SingleAccessKeyChanges { keys } => keys.is_empty(),
+            };
+            if is_empty {
+                continue;
+            }
+
+            let resp = client.changes(block_reference.clone(), request).await?;
+            for change in resp.changes {
+                if let Some(change) = StateChange::from_view(change.value) {
+                    changes.push(change);
+                }
+            }
+        }
+
+        Ok(StateChanges {
+            block_hash: *block.hash(),
+            block_height: block.height(),
+            changes,
+        })
+    }
+}
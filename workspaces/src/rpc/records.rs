@@ -0,0 +1,117 @@
+//! Dumping and loading the state of sandbox accounts as [`StateRecord`]s, the same format
+//! genesis records are given in.
+
+use std::path::Path;
+
+use futures::TryStreamExt;
+use near_jsonrpc_client::methods;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::{BlockId, BlockReference};
+use near_primitives::views::QueryRequest;
+
+use crate::error::{ErrorKind, RpcErrorCode};
+use crate::network::Sandbox;
+use crate::rpc::patch::{DEFAULT_PATCH_PAYLOAD_SIZE, RecordBatches};
+use crate::rpc::stream::StatePageStream;
+use crate::types::account::ContractState;
+use crate::{AccountId, Result, Worker};
+
+/// View the accounts, access keys, code and contract data of `account_ids` as [`StateRecord`]s.
+/// Every account is viewed at the same block, and the records of each account start with its
+/// [`StateRecord::Account`] so they can be patched back in order.
+pub(crate) async fn dump_records(
+    worker: &Worker<Sandbox>,
+    account_ids: Vec<AccountId>,
+) -> Result<Vec<StateRecord>> {
+    let block = worker.view_block().await?;
+    let block_ref: BlockReference =
+        BlockId::Hash(near_primitives::hash::CryptoHash(block.hash().0)).into();
+
+    let mut records = Vec::new();
+    for account_id in account_ids {
+        let account = worker
+            .view_account(&account_id)
+            .block_hash(*block.hash())
+            .await?;
+        let has_code = matches!(account.contract_state, ContractState::LocalHash(_));
+        records.push(StateRecord::Account {
+            account_id: account_id.clone(),
+            account: account.into_near_account(),
+        });
+
+        // Access keys are viewed as is, so that keys only stored by their handle on chain
+        // are dumped as well.
+        let resp = worker
+            .client()
+            .query(methods::query::RpcQueryRequest {
+                block_reference: block_ref.clone(),
+                request: QueryRequest::ViewAccessKeyList {
+                    account_id: account_id.clone(),
+                },
+            })
+            .await
+            .map_err(|err| RpcErrorCode::QueryFailure.custom(err))?;
+        let QueryResponseKind::AccessKeyList(keys) = resp.kind else {
+            return Err(
+                RpcErrorCode::QueryReturnedInvalidData.message("while querying access keys")
+            );
+        };
+        records.extend(keys.keys.into_iter().map(|key| StateRecord::AccessKey {
+            account_id: account_id.clone(),
+            public_key: key.public_key,
+            access_key: key.access_key.into(),
+        }));
+
+        if has_code {
+            let code = worker
+                .view_code(&account_id)
+                .block_hash(*block.hash())
+                .await?;
+            records.push(StateRecord::Contract {
+                account_id: account_id.clone(),
+                code,
+            });
+        }
+
+        let mut pages = StatePageStream::new(worker.clone().coerce(), account_id.clone())
+            .block_reference(block_ref.clone());
+        while let Some(page) = pages.try_next().await? {
+            records.extend(page.into_iter().map(|(key, value)| StateRecord::Data {
+                account_id: account_id.clone(),
+                data_key: key.into(),
+                value: value.into(),
+            }));
+        }
+    }
+
+    Ok(records)
+}
+
+/// Patch `records` into sandbox in order, in batches within the payload limit of the RPC.
+pub(crate) async fn load_records(
+    worker: &Worker<Sandbox>,
+    records: Vec<StateRecord>,
+) -> Result<()> {
+    let mut batches = RecordBatches::new(worker, DEFAULT_PATCH_PAYLOAD_SIZE);
+    for record in records {
+        batches.push(record).await?;
+    }
+    batches.finish().await?;
+    Ok(())
+}
+
+pub(crate) async fn write_records(path: &Path, records: &[StateRecord]) -> Result<()> {
+    let bytes =
+        serde_json::to_vec_pretty(records).map_err(|err| ErrorKind::DataConversion.custom(err))?;
+    tokio::fs::write(path, bytes)
+        .await
+        .map_err(|err| ErrorKind::Io.custom(err))
+}
+
+pub(crate) async fn read_records(path: &Path) -> Result<Vec<StateRecord>> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|err| ErrorKind::Io.custom(err))?;
+    serde_json::from_slice(&bytes).map_err(|err| ErrorKind::DataConversion.custom(err))
+}
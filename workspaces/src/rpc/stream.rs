@@ -0,0 +1,710 @@
+//! Streams that follow the chain of a network as it advances, such as the one returned
+//! from [`Worker::blocks`]. These are builder-like objects that can be further configured
+//! before being polled, and start making requests to the network only once polled.
+//!
+//! To consume them, use the combinators found in [`futures::StreamExt`]:
+//! ```
+//! use futures::StreamExt;
+//! use near_workspaces::{Network, Worker};
+//!
+//! async fn my_func(worker: &Worker<impl Network + 'static>) -> anyhow::Result<()> {
+//!     let mut blocks = worker.blocks();
+//!     while let Some(block) = blocks.next().await {
+//!         println!("new block at height {}", block?.height());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`Worker::blocks`]: crate::Worker::blocks
+
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
+use near_jsonrpc_client::methods;
+use near_jsonrpc_client::methods::block::RpcBlockError;
+use near_jsonrpc_client::methods::tx::TransactionInfo;
+use near_jsonrpc_primitives::types::chunks::ChunkReference;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::types::{BlockId, BlockReference, StoreKey};
+use near_primitives::views::{
+    ActionView, FinalExecutionOutcomeViewEnum, QueryRequest, SignedTransactionView,
+    TxExecutionStatus,
+};
+
+use crate::error::RpcErrorCode;
+use crate::result::Result;
+use crate::rpc::query::StateChangesFilter;
+use crate::types::{AccountActivity, BlockHeight, Finality, StateChanges};
+use crate::{AccountId, Block, CryptoHash, Network, Worker};
+
+/// Default amount of time to wait in between polling the network for new blocks.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default number of blocks to be fetched concurrently by a [`BlockRangeStream`].
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Default number of keys to be viewed at a time by a [`StatePageStream`], also used when
+/// importing the state of a contract.
+pub(crate) const DEFAULT_STATE_PAGE_SIZE: NonZeroU32 = NonZeroU32::new(500).unwrap();
+
+/// A [`Stream`] yielding every new [`Block`] produced by the network, in order of height.
+/// This object is usually given from calling into [`Worker::blocks`].
+///
+/// The first block yielded is the latest block at the time of the first poll. Afterwards,
+/// the network is polled every [`poll_interval`] and all blocks produced in between are
+/// yielded, including ones that were produced faster than the interval. Errors returned
+/// from the network are yielded as is, and the stream will continue retrying from where it
+/// left off if polled again.
+///
+/// [`Worker::blocks`]: crate::Worker::blocks
+/// [`poll_interval`]: BlockStream::poll_interval
+#[must_use = "streams do nothing unless polled"]
+pub struct BlockStream {
+    worker: Worker<dyn Network>,
+    finality: Finality,
+    poll_interval: Duration,
+    inner: Option<BoxStream<'static, Result<Block>>>,
+}
+
+impl BlockStream {
+    pub(crate) fn new(worker: Worker<dyn Network>) -> Self {
+        Self {
+            worker,
+            finality: Finality::Final,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            inner: None,
+        }
+    }
+
+    /// Specify at which block [`Finality`] new blocks are to be yielded. Defaults to
+    /// [`Finality::Final`], such that blocks yielded will never be reverted.
+    pub fn finality(mut self, finality: Finality) -> Self {
+        self.finality = finality;
+        self
+    }
+
+    /// Specify how long to wait in between polling the network for new blocks. Defaults
+    /// to 500 milliseconds.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+/// Internal state of a [`BlockStream`] carried over between each yielded block.
+struct BlockStreamState {
+    worker: Worker<dyn Network>,
+    block_ref: BlockReference,
+    poll_interval: Duration,
+    /// Height and hash of the last block that was queued up to be yielded.
+    last: Option<(BlockHeight, CryptoHash)>,
+    pending: VecDeque<Block>,
+}
+
+impl BlockStreamState {
+    async fn next(mut self) -> Option<(Result<Block>, Self)> {
+        loop {
+            if let Some(block) = self.pending.pop_front() {
+                return Some((Ok(block), self));
+            }
+
+            if self.last.is_some() {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+
+            if let Err(err) = self.poll_new_blocks().await {
+                return Some((Err(err), self));
+            }
+        }
+    }
+
+    /// Fetch the latest block, and walk back the chain till the last block we have seen,
+    /// queueing up every block in between. Walking back by hash rather than by height is
+    /// required since heights can be skipped, such as after a sandbox fast forward.
+    async fn poll_new_blocks(&mut self) -> Result<()> {
+        let head: Block = self
+            .worker
+            .client()
+            .view_block(Some(self.block_ref.clone()))
+            .await?
+            .into();
+        let Some((last_height, last_hash)) = self.last else {
+            self.last = Some((head.height(), *head.hash()));
+            self.pending.push_back(head);
+            return Ok(());
+        };
+
+        if head.height() <= last_height {
+            return Ok(());
+        }
+
+        let mut blocks = vec![head];
+        loop {
+            let earliest = blocks.last().expect("always contains the head block");
+            if *earliest.header().prev_hash() == last_hash || earliest.height() <= last_height + 1 {
+                break;
+            }
+
+            let prev_hash = *earliest.header().prev_hash();
+            let prev = self
+                .worker
+                .client()
+                .view_block(Some(
+                    BlockId::Hash(near_primitives::hash::CryptoHash(prev_hash.0)).into(),
+                ))
+                .await?;
+            blocks.push(prev.into());
+        }
+
+        let head = &blocks[0];
+        self.last = Some((head.height(), *head.hash()));
+        self.pending.extend(blocks.into_iter().rev());
+        Ok(())
+    }
+}
+
+impl Stream for BlockStream {
+    type Item = Result<Block>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            let state = BlockStreamState {
+                worker: this.worker.clone(),
+                block_ref: this.finality.clone().into(),
+                poll_interval: this.poll_interval,
+                last: None,
+                pending: VecDeque::new(),
+            };
+            stream::unfold(state, BlockStreamState::next).boxed()
+        });
+
+        inner.as_mut().poll_next(cx)
+    }
+}
+
+/// A [`Stream`] yielding every [`Block`] within a range of block heights, in order of height.
+/// This object is usually given from calling into [`Worker::blocks_range`].
+///
+/// Heights that do not have a block associated to them, such as skipped heights, are not
+/// yielded. Note that only archival networks will have the full history while networks like
+/// mainnet or testnet will only have the history from 5 or less epochs ago.
+///
+/// [`Worker::blocks_range`]: crate::Worker::blocks_range
+#[must_use = "streams do nothing unless polled"]
+pub struct BlockRangeStream {
+    worker: Worker<dyn Network>,
+    range: Range<BlockHeight>,
+    concurrency: usize,
+    inner: Option<BoxStream<'static, Result<Block>>>,
+}
+
+impl BlockRangeStream {
+    pub(crate) fn new(worker: Worker<dyn Network>, range: Range<BlockHeight>) -> Self {
+        Self {
+            worker,
+            range,
+            concurrency: DEFAULT_CONCURRENCY,
+            inner: None,
+        }
+    }
+
+    /// Specify the maximum number of blocks to be fetched from the network at the same time.
+    /// Blocks are still yielded in order of height. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+impl Stream for BlockRangeStream {
+    type Item = Result<Block>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            let worker = this.worker.clone();
+            stream::iter(this.range.clone())
+                .map(move |height| {
+                    let worker = worker.clone();
+                    async move { block_at_height(&worker, height).await }
+                })
+                .buffered(this.concurrency)
+                .filter_map(|block| async move { block.transpose() })
+                .boxed()
+        });
+
+        inner.as_mut().poll_next(cx)
+    }
+}
+
+/// A [`Stream`] yielding the typed [`StateChanges`] of every block within a range of block
+/// heights, in order of height. This object is usually given from calling into
+/// [`Worker::state_changes_range`].
+///
+/// Like [`BlockRangeStream`], heights that do not have a block associated to them are not
+/// yielded. Changes can be narrowed down with the same filters found on [`QueryStateChanges`].
+///
+/// [`Worker::state_changes_range`]: crate::Worker::state_changes_range
+/// [`QueryStateChanges`]: crate::rpc::query::QueryStateChanges
+#[must_use = "streams do nothing unless polled"]
+pub struct StateChangesStream {
+    worker: Worker<dyn Network>,
+    blocks: Option<BlockRangeStream>,
+    filter: StateChangesFilter,
+    concurrency: usize,
+    inner: Option<BoxStream<'static, Result<StateChanges>>>,
+}
+
+impl StateChangesStream {
+    pub(crate) fn new(worker: Worker<dyn Network>, range: Range<BlockHeight>) -> Self {
+        Self {
+            blocks: Some(BlockRangeStream::new(worker.clone(), range)),
+            worker,
+            filter: StateChangesFilter::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+            inner: None,
+        }
+    }
+
+    /// Only yield changes made to the state of `account_id`. Can be called multiple times
+    /// to include more accounts.
+    pub fn account(mut self, account_id: &AccountId) -> Self {
+        self.filter.add_account(account_id.clone());
+        self
+    }
+
+    /// Only yield changes made to the state of the given accounts.
+    pub fn accounts(mut self, account_ids: impl IntoIterator<Item = AccountId>) -> Self {
+        for account_id in account_ids {
+            self.filter.add_account(account_id);
+        }
+        self
+    }
+
+    /// Only yield changes made to contract storage keys starting with `prefix`. Note that
+    /// this excludes all other kinds of changes, such as account or access key changes.
+    pub fn key_prefix(mut self, prefix: impl AsRef<[u8]>) -> Self {
+        self.filter.key_prefix = Some(prefix.as_ref().to_vec());
+        self
+    }
+
+    /// Specify the maximum number of blocks to fetch changes for at the same time. Changes
+    /// are still yielded in order of height. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self.blocks = self.blocks.map(|blocks| blocks.concurrency(concurrency));
+        self
+    }
+}
+
+impl Stream for StateChangesStream {
+    type Item = Result<StateChanges>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            let worker = this.worker.clone();
+            let filter = this.filter.clone();
+            this.blocks
+                .take()
+                .expect("blocks are only taken once when building the inner stream")
+                .map(move |block| {
+                    let worker = worker.clone();
+                    let filter = filter.clone();
+                    async move {
+                        filter
+                            .state_changes_in_block(worker.client(), &block?)
+                            .await
+                    }
+                })
+                .buffered(this.concurrency)
+                .boxed()
+        });
+
+        inner.as_mut().poll_next(cx)
+    }
+}
+
+/// A page of key value pairs of a contract's state, ordered by key.
+pub type StatePage = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// A [`Stream`] yielding the state of a contract a page of key value pairs at a time, in
+/// lexicographic order of keys. This object is usually given from calling into
+/// [`Worker::view_state_pages`].
+///
+/// Unlike [`Worker::view_state`], this is able to view states that are larger than the limit
+/// the RPC puts on a single view, by requesting at most [`page_size`] keys at a time. All pages
+/// are viewed at the same block, which is the latest block at the time of the first poll if no
+/// block is specified. Errors returned from the network are yielded as is, and the stream will
+/// resume from the last key it has yielded if polled again.
+///
+/// [`Worker::view_state_pages`]: crate::Worker::view_state_pages
+/// [`Worker::view_state`]: crate::Worker::view_state
+/// [`page_size`]: StatePageStream::page_size
+#[must_use = "streams do nothing unless polled"]
+pub struct StatePageStream {
+    state: Option<StatePageState>,
+    inner: Option<BoxStream<'static, Result<StatePage>>>,
+}
+
+impl StatePageStream {
+    pub(crate) fn new(worker: Worker<dyn Network>, account_id: AccountId) -> Self {
+        Self {
+            state: Some(StatePageState {
+                worker,
+                account_id,
+                prefix: Vec::new(),
+                block_ref: None,
+                after_key: None,
+                page_size: DEFAULT_STATE_PAGE_SIZE,
+                done: false,
+            }),
+            inner: None,
+        }
+    }
+
+    fn state(&mut self) -> &mut StatePageState {
+        self.state
+            .as_mut()
+            .expect("state is only taken once when building the inner stream")
+    }
+
+    /// Only view the keys starting with `prefix`.
+    pub fn prefix(mut self, prefix: &[u8]) -> Self {
+        self.state().prefix = prefix.to_vec();
+        self
+    }
+
+    /// Start viewing from the keys that come strictly after `key`, such as to resume viewing
+    /// from the last key of a page previously yielded.
+    pub fn after_key(mut self, key: &[u8]) -> Self {
+        self.state().after_key = Some(key.to_vec());
+        self
+    }
+
+    /// Specify the maximum number of keys to view at a time. Defaults to 500.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.state().page_size = NonZeroU32::new(page_size).unwrap_or(NonZeroU32::MIN);
+        self
+    }
+
+    /// Specify at which block height to view the state from. Note that only archival
+    /// networks will have the full history while networks like mainnet or testnet will
+    /// only have the history from 5 or less epochs ago.
+    pub fn block_height(mut self, height: BlockHeight) -> Self {
+        self.state().block_ref = Some(BlockId::Height(height).into());
+        self
+    }
+
+    /// Specify at which block hash to view the state from. Note that only archival
+    /// networks will have the full history while networks like mainnet or testnet will
+    /// only have the history from 5 or less epochs ago.
+    pub fn block_hash(mut self, hash: CryptoHash) -> Self {
+        self.state().block_ref =
+            Some(BlockId::Hash(near_primitives::hash::CryptoHash(hash.0)).into());
+        self
+    }
+
+    pub(crate) fn block_reference(mut self, block_ref: BlockReference) -> Self {
+        self.state().block_ref = Some(block_ref);
+        self
+    }
+}
+
+/// Internal state of a [`StatePageStream`] carried over between each yielded page.
+struct StatePageState {
+    worker: Worker<dyn Network>,
+    account_id: AccountId,
+    prefix: Vec<u8>,
+    block_ref: Option<BlockReference>,
+    /// Last key that was yielded, from which the next page starts.
+    after_key: Option<Vec<u8>>,
+    page_size: NonZeroU32,
+    done: bool,
+}
+
+impl StatePageState {
+    async fn next(mut self) -> Option<(Result<StatePage>, Self)> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_page().await {
+            Ok(page) if page.is_empty() => None,
+            Ok(page) => Some((Ok(page), self)),
+            Err(err) => Some((Err(err), self)),
+        }
+    }
+
+    async fn next_page(&mut self) -> Result<StatePage> {
+        let block_ref = match &self.block_ref {
+            Some(BlockReference::BlockId(block_id)) => BlockReference::BlockId(block_id.clone()),
+            // Pin down the block, so that all pages are viewed from the same state.
+            block_ref => {
+                let block = self.worker.client().view_block(block_ref.clone()).await?;
+                let block_ref: BlockReference = BlockId::Hash(block.header.hash).into();
+                self.block_ref = Some(block_ref.clone());
+                block_ref
+            }
+        };
+
+        let resp = self
+            .worker
+            .client()
+            .query_cached(methods::query::RpcQueryRequest {
+                block_reference: block_ref,
+                request: QueryRequest::ViewState {
+                    account_id: self.account_id.clone(),
+                    prefix: StoreKey::from(self.prefix.clone()),
+                    after_key: self.after_key.clone().map(StoreKey::from),
+                    limit: Some(self.page_size),
+                    include_proof: false,
+                },
+            })
+            .await
+            .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
+        let QueryResponseKind::ViewState(state) = resp.kind else {
+            return Err(RpcErrorCode::QueryReturnedInvalidData.message("while querying state"));
+        };
+
+        let len = state.values.len();
+        let mut page = state
+            .values
+            .into_iter()
+            .map(|item| (Vec::from(item.key), Vec::from(item.value)))
+            // Guards against nodes that do not support paging and return the full state.
+            .filter(|(key, _)| self.after_key.as_ref().is_none_or(|after| key > after))
+            .collect::<Vec<_>>();
+        page.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        if len < self.page_size.get() as usize {
+            self.done = true;
+        }
+        if let Some((key, _)) = page.last() {
+            self.after_key = Some(key.clone());
+        }
+
+        Ok(page)
+    }
+}
+
+impl Stream for StatePageStream {
+    type Item = Result<StatePage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            let state = this
+                .state
+                .take()
+                .expect("state is only taken once when building the inner stream");
+            stream::unfold(state, StatePageState::next).boxed()
+        });
+
+        inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Fetch the block at the given height, returning `None` if there is no block at that height.
+async fn block_at_height(
+    worker: &Worker<dyn Network>,
+    height: BlockHeight,
+) -> Result<Option<Block>> {
+    let result = worker
+        .client()
+        .query(methods::block::RpcBlockRequest {
+            block_reference: BlockId::Height(height).into(),
+        })
+        .await;
+
+    match result {
+        Ok(view) => Ok(Some(view.into())),
+        Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcBlockError::UnknownBlock { .. },
+        ))) => Ok(None),
+        Err(err) => Err(RpcErrorCode::QueryFailure.custom(err)),
+    }
+}
+
+/// A [`Stream`] yielding every receipt executed on an account, along with the outcome of its
+/// execution, as new blocks are produced by the network. This object is usually given from
+/// calling into [`Worker::watch_account`].
+///
+/// Receipts are discovered by walking the chunks of every new block, and looking up the
+/// execution of each transaction found within them that can reach the account: those signed
+/// by or sent to it, and those calling a contract or relaying a meta transaction, which can
+/// make calls of their own. Receipts are yielded once the execution of the transaction they
+/// belong to has completed, at the [`Finality`] the stream watches blocks at, so receipts
+/// executed on the account are only found if their transaction was included in a block
+/// produced after the first poll.
+///
+/// [`Worker::watch_account`]: crate::Worker::watch_account
+#[must_use = "streams do nothing unless polled"]
+pub struct AccountActivityStream {
+    worker: Worker<dyn Network>,
+    account_id: AccountId,
+    blocks: Option<BlockStream>,
+    finality: Finality,
+    inner: Option<BoxStream<'static, Result<AccountActivity>>>,
+}
+
+impl AccountActivityStream {
+    pub(crate) fn new(worker: Worker<dyn Network>, account_id: AccountId) -> Self {
+        Self {
+            blocks: Some(BlockStream::new(worker.clone())),
+            worker,
+            account_id,
+            finality: Finality::Final,
+            inner: None,
+        }
+    }
+
+    /// Specify at which block [`Finality`] new blocks are to be watched. Defaults to
+    /// [`Finality::Final`].
+    pub fn finality(mut self, finality: Finality) -> Self {
+        self.blocks = self.blocks.map(|blocks| blocks.finality(finality.clone()));
+        self.finality = finality;
+        self
+    }
+
+    /// Specify how long to wait in between polling the network for new blocks. Defaults
+    /// to 500 milliseconds.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.blocks = self.blocks.map(|blocks| blocks.poll_interval(interval));
+        self
+    }
+}
+
+impl Stream for AccountActivityStream {
+    type Item = Result<AccountActivity>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            let worker = this.worker.clone();
+            let account_id = this.account_id.clone();
+            // Wait for transactions to be executed only as final as the blocks watched.
+            let wait_until = match this.finality {
+                Finality::Final => TxExecutionStatus::Final,
+                Finality::Optimistic | Finality::DoomSlug => TxExecutionStatus::ExecutedOptimistic,
+            };
+            this.blocks
+                .take()
+                .expect("blocks are only taken once when building the inner stream")
+                .then(move |block| {
+                    let worker = worker.clone();
+                    let account_id = account_id.clone();
+                    let wait_until = wait_until.clone();
+                    async move {
+                        account_activity_in_block(&worker, &account_id, &block?, wait_until).await
+                    }
+                })
+                .flat_map(|activities| {
+                    let activities = match activities {
+                        Ok(activities) => activities.into_iter().map(Ok).collect(),
+                        Err(err) => vec![Err(err)],
+                    };
+                    stream::iter(activities)
+                })
+                .boxed()
+        });
+
+        inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Find all the receipts executed on `account_id` from the transactions included in `block`,
+/// once their execution reached `wait_until`.
+pub(crate) async fn account_activity_in_block(
+    worker: &Worker<dyn Network>,
+    account_id: &AccountId,
+    block: &Block,
+    wait_until: TxExecutionStatus,
+) -> Result<Vec<AccountActivity>> {
+    // Only chunks included in this block are new, the rest are carried over from previous blocks.
+    let chunks = block
+        .chunks()
+        .iter()
+        .filter(|chunk| chunk.height_included == block.height())
+        .map(|chunk| {
+            worker.client().query(methods::chunk::RpcChunkRequest {
+                chunk_reference: ChunkReference::ChunkHash {
+                    chunk_id: near_primitives::hash::CryptoHash(chunk.chunk_hash.0),
+                },
+            })
+        });
+    let chunks = futures::future::try_join_all(chunks)
+        .await
+        .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
+
+    let transactions = chunks
+        .into_iter()
+        .flat_map(|chunk| chunk.transactions)
+        .filter(|tx| may_reach_account(tx, account_id))
+        .map(|tx| {
+            worker.client().query(
+                methods::EXPERIMENTAL_tx_status::RpcTransactionStatusRequest {
+                    transaction_info: TransactionInfo::TransactionId {
+                        tx_hash: tx.hash,
+                        sender_account_id: tx.signer_id,
+                    },
+                    wait_until: wait_until.clone(),
+                },
+            )
+        });
+    let transactions = futures::future::try_join_all(transactions)
+        .await
+        .map_err(|e| RpcErrorCode::QueryFailure.custom(e))?;
+
+    let mut activities = Vec::new();
+    for tx in transactions {
+        let Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome)) =
+            tx.final_execution_outcome
+        else {
+            return Err(RpcErrorCode::QueryReturnedInvalidData
+                .message("while querying transaction status with receipts"));
+        };
+
+        let tx_hash = outcome.final_outcome.transaction_outcome.id.into();
+        let mut receipt_outcomes = outcome.final_outcome.receipts_outcome;
+        for receipt in outcome.receipts {
+            if receipt.receiver_id != *account_id {
+                continue;
+            }
+
+            let Some(idx) = receipt_outcomes
+                .iter()
+                .position(|outcome| outcome.id == receipt.receipt_id)
+            else {
+                continue;
+            };
+            let receipt_outcome = receipt_outcomes.swap_remove(idx);
+            activities.push(AccountActivity::new(tx_hash, receipt, receipt_outcome));
+        }
+    }
+
+    Ok(activities)
+}
+
+/// Whether the execution of `tx` can produce receipts executed on `account_id`: it is signed
+/// by or sent to the account, calls a contract or relays a meta transaction, either of which
+/// can send receipts anywhere, or gives the balance of a deleted account to it.
+fn may_reach_account(tx: &SignedTransactionView, account_id: &AccountId) -> bool {
+    tx.signer_id == *account_id
+        || tx.receiver_id == *account_id
+        || tx.actions.iter().any(|action| match action {
+            ActionView::FunctionCall { .. }
+            | ActionView::Delegate { .. }
+            | ActionView::DelegateV2 { .. } => true,
+            ActionView::DeleteAccount { beneficiary_id } => beneficiary_id == account_id,
+            _ => false,
+        })
+}
@@ -4,8 +4,13 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use chrono::Utc;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use near_crypto::SecretKey;
@@ -95,6 +100,174 @@ pub(crate) async fn url_create_account(
 }
 
 pub(crate) fn write_cred_to_file(path: &Path, id: &AccountId, sk: &SecretKey) -> Result<()> {
+    let content = serde_json::json!({
+        "account_id": id,
+        "public_key": sk.public_key(),
+        "secret_key": sk,
+    })
+    .to_string()
+    .into_bytes();
+
+    write_private_file(path, &content)
+}
+
+/// Key derivation used for the credential files written by [`write_encrypted_cred_to_file`].
+const CREDENTIALS_KDF: &str = "argon2id";
+
+/// Cipher used for the credential files written by [`write_encrypted_cred_to_file`].
+const CREDENTIALS_CIPHER: &str = "chacha20-poly1305";
+
+/// Credentials file holding a secret key encrypted with a key derived from a passphrase. The
+/// account ID and public key are kept in clear, and the account ID is authenticated along
+/// with the secret key.
+#[derive(Serialize, Deserialize)]
+struct EncryptedCredentials {
+    account_id: AccountId,
+    public_key: near_crypto::PublicKey,
+    kdf: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub(crate) fn write_encrypted_cred_to_file(
+    path: &Path,
+    id: &AccountId,
+    sk: &SecretKey,
+    passphrase: &str,
+) -> Result<()> {
+    let params = argon2::Params::default();
+    let mut salt = [0; 16];
+    let mut nonce = [0; 12];
+    rand::thread_rng().fill(&mut salt);
+    rand::thread_rng().fill(&mut nonce);
+
+    let cipher = credentials_cipher(passphrase, &salt, params.clone())?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: sk.to_string().as_bytes(),
+                aad: id.as_bytes(),
+            },
+        )
+        .map_err(|_| ErrorKind::DataConversion.message("failed to encrypt credentials"))?;
+
+    let credentials = EncryptedCredentials {
+        account_id: id.clone(),
+        public_key: sk.public_key(),
+        kdf: CREDENTIALS_KDF.into(),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        salt: general_purpose::STANDARD.encode(salt),
+        cipher: CREDENTIALS_CIPHER.into(),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    let content =
+        serde_json::to_vec(&credentials).map_err(|err| ErrorKind::DataConversion.custom(err))?;
+
+    write_private_file(path, &content)
+}
+
+pub(crate) fn read_encrypted_cred_from_file(
+    path: &Path,
+    passphrase: &str,
+) -> Result<(AccountId, SecretKey)> {
+    let content = std::fs::read(path).map_err(|err| {
+        ErrorKind::Io.full(format!("failed to read credentials from {path:?}"), err)
+    })?;
+    let credentials: EncryptedCredentials = serde_json::from_slice(&content)
+        .map_err(|err| ErrorKind::DataConversion.full("invalid encrypted credentials", err))?;
+    if credentials.kdf != CREDENTIALS_KDF || credentials.cipher != CREDENTIALS_CIPHER {
+        return Err(ErrorKind::DataConversion.message(format!(
+            "unsupported encryption of credentials: {} with {}",
+            credentials.cipher, credentials.kdf
+        )));
+    }
+
+    let decode = |value: &str| {
+        general_purpose::STANDARD
+            .decode(value)
+            .map_err(|err| ErrorKind::DataConversion.full("invalid encrypted credentials", err))
+    };
+    let salt = decode(&credentials.salt)?;
+    let nonce = decode(&credentials.nonce)?;
+    let ciphertext = decode(&credentials.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(ErrorKind::DataConversion.message("invalid encrypted credentials nonce"));
+    }
+
+    // The parameters come from the file, so cap them at those the files are written with,
+    // rather than deriving the key with however much memory and time a corrupted or hostile
+    // file asks for.
+    if credentials.m_cost > argon2::Params::DEFAULT_M_COST
+        || credentials.t_cost > argon2::Params::DEFAULT_T_COST
+        || credentials.p_cost > argon2::Params::DEFAULT_P_COST
+    {
+        return Err(ErrorKind::DataConversion.message(format!(
+            "key derivation parameters of credentials exceed the maximum: m_cost={}, t_cost={}, p_cost={}",
+            credentials.m_cost, credentials.t_cost, credentials.p_cost
+        )));
+    }
+    let params = argon2::Params::new(
+        credentials.m_cost,
+        credentials.t_cost,
+        credentials.p_cost,
+        None,
+    )
+    .map_err(|err| {
+        ErrorKind::DataConversion.message(format!("invalid key derivation parameters: {err}"))
+    })?;
+    let cipher = credentials_cipher(passphrase, &salt, params)?;
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: credentials.account_id.as_bytes(),
+            },
+        )
+        .map_err(|_| {
+            ErrorKind::DataConversion
+                .message("failed to decrypt credentials: wrong passphrase or corrupted file")
+        })?;
+
+    let sk = std::str::from_utf8(&plaintext)
+        .ok()
+        .and_then(|sk| sk.parse::<SecretKey>().ok())
+        .ok_or_else(|| ErrorKind::DataConversion.message("invalid decrypted secret key"))?;
+    if sk.public_key() != credentials.public_key {
+        return Err(ErrorKind::DataConversion
+            .message("decrypted secret key does not match the public key of the credentials"));
+    }
+
+    Ok((credentials.account_id, sk))
+}
+
+/// Derive the cipher of encrypted credentials from `passphrase`.
+fn credentials_cipher(
+    passphrase: &str,
+    salt: &[u8],
+    params: argon2::Params,
+) -> Result<ChaCha20Poly1305> {
+    let mut key = [0; 32];
+    Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| {
+            ErrorKind::DataConversion
+                .message(format!("failed to derive the credentials key: {err}"))
+        })?;
+    Ok(ChaCha20Poly1305::new(&key.into()))
+}
+
+/// Write `content` to a new file at `path`, only readable and writable by its owner.
+fn write_private_file(path: &Path, content: &[u8]) -> Result<()> {
     let mut file = File::create(path).map_err(|err| {
         ErrorKind::Io.full(
             format!("failed to open {path:?} for writing credentials"),
@@ -119,14 +292,6 @@ pub(crate) fn write_cred_to_file(path: &Path, id: &AccountId, sk: &SecretKey) ->
             .map_err(|err| ErrorKind::Io.full("Failed to set permissions for a key file.", err))?;
     }
 
-    let content = serde_json::json!({
-        "account_id": id,
-        "public_key": sk.public_key(),
-        "secret_key": sk,
-    })
-    .to_string()
-    .into_bytes();
-
-    file.write_all(&content)
+    file.write_all(content)
         .map_err(|err| ErrorKind::Io.full("Failed to write a key file", err))
 }
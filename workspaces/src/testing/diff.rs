@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use crate::operations::Function;
+use crate::result::{ExecutionFinalResult, Result, ViewResultDetails};
+use crate::testing::upgrade::view_full_state;
+use crate::types::{Gas, StateDiff, ValueChange};
+use crate::{Account, AccountId, Contract, DevNetwork, Worker};
+
+/// Harness for differential testing of two versions of a contract, such as before and after
+/// a refactor or an SDK upgrade. Each version is deployed to its own dev account, then the
+/// same sequence of [`call`]s and [`view`]s is made against both, with the calls all being
+/// signed by the same [`caller`]. Any difference in their outcomes is reported as a
+/// [`Divergence`].
+///
+/// Since both versions are deployed to different accounts, the account id of the contract
+/// running `b` is replaced with that of `a` before comparing the outcomes, so that contracts
+/// referring to their own account id do not diverge.
+///
+/// [`call`]: DiffTest::call
+/// [`view`]: DiffTest::view
+/// [`caller`]: DiffTest::caller
+pub struct DiffTest<T> {
+    worker: Worker<T>,
+    wasm_a: Vec<u8>,
+    wasm_b: Vec<u8>,
+    caller: Option<Account>,
+    steps: Vec<Step>,
+    gas_tolerance: u64,
+}
+
+enum Step {
+    Call(Function),
+    View(Function),
+}
+
+impl<T> DiffTest<T>
+where
+    T: DevNetwork + 'static,
+{
+    /// Create a harness comparing the contract `wasm_a` against `wasm_b`.
+    pub fn new(worker: &Worker<T>, wasm_a: &[u8], wasm_b: &[u8]) -> Self {
+        Self {
+            worker: worker.clone(),
+            wasm_a: wasm_a.to_vec(),
+            wasm_b: wasm_b.to_vec(),
+            caller: None,
+            steps: Vec::new(),
+            gas_tolerance: 0,
+        }
+    }
+
+    /// The account signing every call. Defaults to a new dev account.
+    pub fn caller(mut self, caller: &Account) -> Self {
+        self.caller = Some(caller.clone());
+        self
+    }
+
+    /// Call `function` on both contracts, comparing their return values, logs, failures,
+    /// gas burnt and resulting state.
+    pub fn call(mut self, function: Function) -> Self {
+        self.steps.push(Step::Call(function));
+        self
+    }
+
+    /// View `function` on both contracts, comparing their return values, logs and failures.
+    pub fn view(mut self, function: Function) -> Self {
+        self.steps.push(Step::View(function));
+        self
+    }
+
+    /// Percentage by which the gas burnt by a call may differ between both contracts before
+    /// being reported as a divergence. Defaults to 0, reporting any difference.
+    pub fn gas_tolerance(mut self, percent: u64) -> Self {
+        self.gas_tolerance = percent;
+        self
+    }
+
+    /// Run every step against both contracts, and report on how their outcomes diverged.
+    pub async fn run(self) -> Result<DiffReport> {
+        let contract_a = self.worker.dev_deploy(&self.wasm_a).await?;
+        let contract_b = self.worker.dev_deploy(&self.wasm_b).await?;
+        let caller = match self.caller {
+            Some(caller) => caller,
+            None => self.worker.dev_create_account().await?,
+        };
+        let normalize = Normalizer {
+            from: contract_b.id().clone(),
+            to: contract_a.id().clone(),
+        };
+
+        let mut divergences = Vec::new();
+        for (step, kind) in self.steps.into_iter().enumerate() {
+            let (function, outcomes) = match kind {
+                Step::Call(function) => {
+                    let (name, args) = (function.name.clone(), function.args?);
+                    let call = |contract: &Contract| {
+                        caller
+                            .batch(contract.id())
+                            .call(Function {
+                                name: name.clone(),
+                                args: Ok(args.clone()),
+                                deposit: function.deposit,
+                                gas: function.gas,
+                            })
+                            .transact()
+                    };
+                    let a = Outcome::from_call(call(&contract_a).await?);
+                    let b = Outcome::from_call(call(&contract_b).await?).normalize(&normalize);
+
+                    let exceeds_tolerance = a.gas.as_gas().abs_diff(b.gas.as_gas()) * 100
+                        > a.gas.as_gas() * self.gas_tolerance;
+                    if a.gas != b.gas && exceeds_tolerance {
+                        divergences.push(Divergence {
+                            step,
+                            function: name.clone(),
+                            kind: DivergenceKind::Gas { a: a.gas, b: b.gas },
+                        });
+                    }
+
+                    let state_a = view_full_state(&contract_a).await?;
+                    let state_b = view_full_state(&contract_b).await?;
+                    let diff = state_diff(state_a, normalize.state(state_b));
+                    if !diff.is_empty() {
+                        divergences.push(Divergence {
+                            step,
+                            function: name.clone(),
+                            kind: DivergenceKind::State(diff),
+                        });
+                    }
+
+                    (name, (a, b))
+                }
+                Step::View(function) => {
+                    let args = function.args?;
+                    let a = contract_a.view(&function.name).args(args.clone()).await;
+                    let b = contract_b.view(&function.name).args(args).await;
+                    let a = Outcome::from_view(a);
+                    let b = Outcome::from_view(b).normalize(&normalize);
+                    (function.name, (a, b))
+                }
+            };
+
+            let (a, b) = outcomes;
+            let mut push = |kind| {
+                divergences.push(Divergence {
+                    step,
+                    function: function.clone(),
+                    kind,
+                })
+            };
+            if a.failure != b.failure {
+                push(DivergenceKind::Failure {
+                    a: a.failure,
+                    b: b.failure,
+                });
+            } else if a.value != b.value {
+                push(DivergenceKind::ReturnValue {
+                    a: a.value,
+                    b: b.value,
+                });
+            }
+            if a.logs != b.logs {
+                push(DivergenceKind::Logs {
+                    a: a.logs,
+                    b: b.logs,
+                });
+            }
+        }
+        divergences.sort_by_key(|divergence| divergence.step);
+
+        Ok(DiffReport {
+            contract_a,
+            contract_b,
+            divergences,
+        })
+    }
+}
+
+/// The outcome of running a [`DiffTest`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct DiffReport {
+    /// The contract running the `a` version.
+    pub contract_a: Contract,
+    /// The contract running the `b` version.
+    pub contract_b: Contract,
+    /// Every divergence found, ordered by the step it was found in.
+    pub divergences: Vec<Divergence>,
+}
+
+impl DiffReport {
+    /// Returns true if both contracts behaved identically.
+    pub fn is_identical(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// A difference in the outcome of a single step of a [`DiffTest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Divergence {
+    /// Index of the step, in the order the calls and views were added.
+    pub step: usize,
+    /// Name of the function called or viewed by the step.
+    pub function: String,
+    /// What diverged.
+    pub kind: DivergenceKind,
+}
+
+/// What diverged within a [`Divergence`], along with the outcome of each contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DivergenceKind {
+    /// Both succeeded, but returned different values.
+    ReturnValue { a: Vec<u8>, b: Vec<u8> },
+    /// Different logs were emitted, which includes any events.
+    Logs { a: Vec<String>, b: Vec<String> },
+    /// Only one of them failed, or both failed with different errors.
+    Failure {
+        a: Option<String>,
+        b: Option<String>,
+    },
+    /// The state after a call differs, with the state of `a` taken as the old state and that
+    /// of `b` as the new one.
+    State(StateDiff),
+    /// The gas burnt by a call differs by more than the tolerance.
+    Gas { a: Gas, b: Gas },
+}
+
+/// The comparable parts of the outcome of a step.
+struct Outcome {
+    value: Vec<u8>,
+    logs: Vec<String>,
+    failure: Option<String>,
+    gas: Gas,
+}
+
+impl Outcome {
+    fn from_call(result: ExecutionFinalResult) -> Self {
+        let logs = result.logs().into_iter().map(String::from).collect();
+        let gas = result.total_gas_burnt;
+        let (value, failure) = match result.into_result() {
+            Ok(success) => (success.raw_bytes().unwrap_or_default(), None),
+            Err(failure) => (Vec::new(), Some(failure.to_string())),
+        };
+        Self {
+            value,
+            logs,
+            failure,
+            gas,
+        }
+    }
+
+    fn from_view(result: Result<ViewResultDetails>) -> Self {
+        let (value, logs, failure) = match result {
+            Ok(details) => (details.result, details.logs, None),
+            Err(err) => (Vec::new(), Vec::new(), Some(err.to_string())),
+        };
+        Self {
+            value,
+            logs,
+            failure,
+            gas: Gas::from_gas(0),
+        }
+    }
+
+    fn normalize(mut self, normalize: &Normalizer) -> Self {
+        self.value = normalize.bytes(&self.value);
+        self.logs = self.logs.iter().map(|log| normalize.str(log)).collect();
+        self.failure = self.failure.map(|failure| normalize.str(&failure));
+        self
+    }
+}
+
+/// Replaces the account id of one contract with the other's.
+struct Normalizer {
+    from: AccountId,
+    to: AccountId,
+}
+
+impl Normalizer {
+    fn str(&self, s: &str) -> String {
+        s.replace(self.from.as_str(), self.to.as_str())
+    }
+
+    fn bytes(&self, bytes: &[u8]) -> Vec<u8> {
+        let (from, to) = (self.from.as_bytes(), self.to.as_bytes());
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i..].starts_with(from) {
+                out.extend_from_slice(to);
+                i += from.len();
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        out
+    }
+
+    fn state(&self, state: HashMap<Vec<u8>, Vec<u8>>) -> HashMap<Vec<u8>, Vec<u8>> {
+        state
+            .into_iter()
+            .map(|(key, value)| (self.bytes(&key), self.bytes(&value)))
+            .collect()
+    }
+}
+
+fn state_diff(a: HashMap<Vec<u8>, Vec<u8>>, mut b: HashMap<Vec<u8>, Vec<u8>>) -> StateDiff {
+    let mut diff = StateDiff::default();
+    for (key, old) in a {
+        match b.remove(&key) {
+            None => {
+                diff.removed.insert(key, old);
+            }
+            Some(new) if new != old => {
+                diff.modified.insert(key, ValueChange { old, new });
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = b;
+    diff
+}
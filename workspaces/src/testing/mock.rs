@@ -0,0 +1,455 @@
+use std::sync::Arc;
+
+use near_primitives::borsh;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+use wasm_encoder::{
+    CodeSection, ExportKind, ExportSection, FunctionSection, Instruction, RawSection,
+};
+use wasmparser::{ExternalKind, Parser, Payload, TypeRef};
+
+use crate::error::ErrorKind;
+use crate::operations::Function;
+use crate::result::Result;
+use crate::types::{Gas, NearToken};
+use crate::{AccountId, Contract};
+
+/// Prefix of the storage keys of recorded calls, each followed by the little endian `u64`
+/// index of the call and then the name of the method called.
+const CALL_PREFIX: &[u8] = b"c";
+
+/// The mock contract built from `examples/mock-contract`, exporting none of the mocked
+/// methods. Each is exported by [`MockContract::wasm`] as a call to [`HANDLE_EXPORT`].
+const MOCK_WASM: &[u8] = include_bytes!("../../res/mock.wasm");
+
+/// Export of the mock recording a call to the method at the index it is given, then
+/// responding as configured. It is removed from the deployed wasm, as it takes a parameter.
+const HANDLE_EXPORT: &str = "mock_handle";
+
+/// Method of the mock storing the configuration of a method, callable by the mock only.
+const CONFIGURE_METHOD: &str = "mock_configure";
+
+// Kinds of response of a configured method, as understood by `mock_handle`.
+const RETURN_VALUE: u8 = 0;
+const PANIC: u8 = 1;
+const RETURN_PROMISE: u8 = 2;
+
+/// A contract whose methods and their responses are programmed from the test, for stubbing
+/// out the contracts called by the contract under test. Usually given from calling into
+/// [`Worker::deploy_mock`].
+///
+/// Every method is programmed with [`on`], which stores its response in the state of the mock.
+/// Programming a method for the first time also redeploys the mock with the method exported,
+/// in the same transaction. Each call received by the mock is recorded in its state along with its predecessor,
+/// arguments, deposit and gas, to be asserted on with [`calls`]:
+///
+/// ```no_run
+/// use near_workspaces::operations::Function;
+/// use serde_json::json;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let worker = near_workspaces::sandbox().await?;
+/// let token = worker.deploy_mock().await?;
+/// token.on("ft_balance_of").returns_json(&json!("100")).await?;
+/// token.on("ft_transfer").panics("The account doesn't have enough balance").await?;
+///
+/// // ... exercise the contract under test, which calls into `token.id()` ...
+///
+/// let calls = token.calls_to("ft_balance_of").await?;
+/// assert_eq!(calls.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Note that the mock records calls by writing to its state, so its methods can only be
+/// called in transactions rather than viewed. Calls that the mock was programmed to panic on
+/// are not recorded either, since their state changes are reverted along with the panic.
+///
+/// [`Worker::deploy_mock`]: crate::Worker::deploy_mock
+/// [`on`]: MockContract::on
+/// [`calls`]: MockContract::calls
+#[derive(Clone, Debug)]
+pub struct MockContract {
+    contract: Contract,
+    /// Names of the methods exported so far, in the order of their index. Locked for as long
+    /// as a method is being programmed, so that concurrent calls can't deploy a stale list.
+    methods: Arc<Mutex<Vec<String>>>,
+}
+
+#[derive(Clone, Debug)]
+enum Response {
+    Value(Vec<u8>),
+    Panic(String),
+    Promise {
+        receiver_id: AccountId,
+        name: String,
+        args: Vec<u8>,
+        deposit: NearToken,
+        gas: Gas,
+    },
+}
+
+impl MockContract {
+    pub(crate) fn new(contract: Contract) -> Self {
+        Self {
+            contract,
+            methods: Arc::default(),
+        }
+    }
+
+    /// The wasm of the mock exporting `methods`, which are then configured by calling
+    /// `mock_configure` on the mock. This is what [`MockContract::on`] deploys, such as for
+    /// patching the mock into an account that can't be deployed to.
+    ///
+    /// Each method is appended to the bundled mock contract as a function calling its
+    /// handler with the index of the method in `methods`. Fails if a method is named after
+    /// an export of the mock itself, such as `mock_configure`.
+    pub fn wasm(methods: &[&str]) -> Result<Vec<u8>> {
+        mock_wasm(methods)
+    }
+
+    /// Account ID of the mock.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`] the mock is deployed to.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Program how the mock responds to calls to the method `name`. Programming a method
+    /// again replaces its previous response, without redeploying the mock.
+    pub fn on(&self, name: &str) -> MockMethod<'_> {
+        MockMethod {
+            mock: self,
+            name: name.into(),
+        }
+    }
+
+    /// Every call the mock received, in the order they were received.
+    pub async fn calls(&self) -> Result<Vec<MockCall>> {
+        let state = self.contract.view_state().prefix(CALL_PREFIX).await?;
+        let mut calls = state
+            .into_iter()
+            .map(|(key, value)| MockCall::from_record(&key, &value))
+            .collect::<Result<Vec<_>>>()?;
+        calls.sort_by_key(|(index, _)| *index);
+        Ok(calls.into_iter().map(|(_, call)| call).collect())
+    }
+
+    /// Every call the mock received to the method `name`, in the order they were received.
+    pub async fn calls_to(&self, name: &str) -> Result<Vec<MockCall>> {
+        let mut calls = self.calls().await?;
+        calls.retain(|call| call.method == name);
+        Ok(calls)
+    }
+
+    async fn respond(&self, name: String, response: Response) -> Result<()> {
+        let mut methods = self.methods.lock().await;
+        let (index, wasm) = match methods.iter().position(|method| *method == name) {
+            Some(index) => (index, None),
+            None => {
+                let mut names = methods.iter().map(String::as_str).collect::<Vec<_>>();
+                names.push(&name);
+                (methods.len(), Some(mock_wasm(&names)?))
+            }
+        };
+
+        let mut args = (index as u32).to_le_bytes().to_vec();
+        args.extend(response.config(&name));
+        let mut tx = self.contract.batch();
+        if let Some(wasm) = &wasm {
+            tx = tx.deploy(wasm);
+        }
+        tx.call(Function::new(CONFIGURE_METHOD).args(args))
+            .transact()
+            .await?
+            .into_result()?;
+
+        // Only a method deployed along with its configuration is exported from now on.
+        if wasm.is_some() {
+            methods.push(name);
+        }
+        Ok(())
+    }
+}
+
+impl Response {
+    /// The configuration of the method `name` responding with `self`, as laid out for
+    /// `mock_configure`: the length of the name (`u32`) and the name, the kind of response
+    /// (`u8`) and then the response itself.
+    fn config(&self, name: &str) -> Vec<u8> {
+        let mut config = Vec::new();
+        push_bytes(&mut config, name.as_bytes());
+        match self {
+            Response::Value(value) => {
+                config.push(RETURN_VALUE);
+                config.extend_from_slice(value);
+            }
+            Response::Panic(message) => {
+                config.push(PANIC);
+                config.extend_from_slice(message.as_bytes());
+            }
+            Response::Promise {
+                receiver_id,
+                name,
+                args,
+                deposit,
+                gas,
+            } => {
+                config.push(RETURN_PROMISE);
+                push_bytes(&mut config, receiver_id.as_bytes());
+                push_bytes(&mut config, name.as_bytes());
+                config.extend_from_slice(&deposit.as_yoctonear().to_le_bytes());
+                config.extend_from_slice(&gas.as_gas().to_le_bytes());
+                config.extend_from_slice(args);
+            }
+        }
+        config
+    }
+}
+
+/// Append `bytes` prefixed by their little endian `u32` length.
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// A method of a [`MockContract`] being programmed, given from calling into
+/// [`MockContract::on`]. Each response is stored in the state of the mock.
+pub struct MockMethod<'a> {
+    mock: &'a MockContract,
+    name: String,
+}
+
+impl MockMethod<'_> {
+    /// Return `value` serialized as JSON.
+    pub async fn returns_json<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        let value = serde_json::to_vec(value).map_err(|e| ErrorKind::DataConversion.custom(e))?;
+        self.returns(value).await
+    }
+
+    /// Return `value` serialized with borsh.
+    pub async fn returns_borsh<T: borsh::BorshSerialize + ?Sized>(self, value: &T) -> Result<()> {
+        let value = borsh::to_vec(value).map_err(|e| ErrorKind::DataConversion.custom(e))?;
+        self.returns(value).await
+    }
+
+    /// Return the raw bytes of `value`.
+    pub async fn returns(self, value: impl Into<Vec<u8>>) -> Result<()> {
+        self.mock
+            .respond(self.name, Response::Value(value.into()))
+            .await
+    }
+
+    /// Panic with `message`, failing the call.
+    pub async fn panics(self, message: &str) -> Result<()> {
+        self.mock
+            .respond(self.name, Response::Panic(message.into()))
+            .await
+    }
+
+    /// Call `function` on `receiver_id`, and return whatever it returns. This is useful for
+    /// checking how the contract under test handles results only available after some
+    /// blocks, or the failure of a promise further down the chain.
+    pub async fn returns_after_promise(
+        self,
+        receiver_id: &AccountId,
+        function: Function,
+    ) -> Result<()> {
+        let response = Response::Promise {
+            receiver_id: receiver_id.clone(),
+            args: function.args?,
+            name: function.name,
+            deposit: function.deposit,
+            gas: function.gas,
+        };
+        self.mock.respond(self.name, response).await
+    }
+}
+
+/// A call received by a [`MockContract`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MockCall {
+    /// Name of the method called.
+    pub method: String,
+    /// The account that made the call.
+    pub predecessor_id: AccountId,
+    /// The raw arguments of the call.
+    pub args: Vec<u8>,
+    /// Deposit attached to the call.
+    pub deposit: NearToken,
+    /// Gas attached to the call.
+    pub gas: Gas,
+}
+
+impl MockCall {
+    /// Deserialize the arguments of the call from JSON.
+    pub fn args_json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.args).map_err(|e| ErrorKind::DataConversion.custom(e))
+    }
+
+    /// Deserialize the arguments of the call from borsh.
+    pub fn args_borsh<T: borsh::BorshDeserialize>(&self) -> Result<T> {
+        borsh::from_slice(&self.args).map_err(|e| ErrorKind::DataConversion.custom(e))
+    }
+
+    /// Parse a call recorded by the mock, as laid out by `examples/mock-contract`, along with
+    /// its index: the key is [`CALL_PREFIX`] ++ index ++ method name, and the value is the
+    /// deposit (`u128`), the gas (`u64`), the length of the predecessor id (`u32`) and the
+    /// predecessor id, followed by the arguments.
+    fn from_record(key: &[u8], value: &[u8]) -> Result<(u64, Self)> {
+        let invalid = || ErrorKind::DataConversion.message("invalid call recorded by mock");
+        let key = key.strip_prefix(CALL_PREFIX).ok_or_else(invalid)?;
+        let (index, method) = key.split_at_checked(8).ok_or_else(invalid)?;
+        let (deposit, rest) = value.split_at_checked(16).ok_or_else(invalid)?;
+        let (gas, rest) = rest.split_at_checked(8).ok_or_else(invalid)?;
+        let (predecessor_len, rest) = rest.split_at_checked(4).ok_or_else(invalid)?;
+        let predecessor_len = u32::from_le_bytes(predecessor_len.try_into().unwrap()) as usize;
+        let (predecessor_id, args) = rest.split_at_checked(predecessor_len).ok_or_else(invalid)?;
+
+        let call = Self {
+            method: String::from_utf8(method.to_vec())
+                .map_err(|e| ErrorKind::DataConversion.custom(e))?,
+            predecessor_id: std::str::from_utf8(predecessor_id)
+                .map_err(|e| ErrorKind::DataConversion.custom(e))?
+                .parse()
+                .map_err(|e| ErrorKind::DataConversion.custom(e))?,
+            args: args.to_vec(),
+            deposit: NearToken::from_yoctonear(u128::from_le_bytes(deposit.try_into().unwrap())),
+            gas: Gas::from_gas(u64::from_le_bytes(gas.try_into().unwrap())),
+        };
+        Ok((u64::from_le_bytes(index.try_into().unwrap()), call))
+    }
+}
+
+/// Generate the wasm of the mock exporting `methods`: for each method, a function calling
+/// [`HANDLE_EXPORT`] with the index of the method is appended to [`MOCK_WASM`] and exported
+/// under its name, while [`HANDLE_EXPORT`] stops being exported. Every other section is
+/// copied as is.
+fn mock_wasm(methods: &[&str]) -> Result<Vec<u8>> {
+    let invalid = |e| ErrorKind::DataConversion.full("invalid mock wasm", e);
+    let missing = |export| {
+        ErrorKind::DataConversion.message(format!("mock wasm is missing the `{export}` export"))
+    };
+
+    let mut imported_functions = 0;
+    let mut function_types = Vec::new();
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(MOCK_WASM) {
+        match payload.map_err(invalid)? {
+            Payload::ImportSection(reader) => {
+                for import in reader.into_imports() {
+                    if let TypeRef::Func(_) | TypeRef::FuncExact(_) = import.map_err(invalid)?.ty {
+                        imported_functions += 1;
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                function_types = reader
+                    .into_iter()
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(invalid)?;
+            }
+            Payload::ExportSection(reader) => {
+                exports = reader
+                    .into_iter()
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(invalid)?;
+            }
+            _ => {}
+        }
+    }
+
+    let export_index = |name| {
+        exports
+            .iter()
+            .find(|export: &&wasmparser::Export| export.name == name)
+            .map(|export| export.index)
+            .ok_or_else(|| missing(name))
+    };
+    let handle = export_index(HANDLE_EXPORT)?;
+    // The mocked methods share the `() -> ()` type of `mock_configure`.
+    let method_type = function_types
+        .get((export_index(CONFIGURE_METHOD)? - imported_functions) as usize)
+        .copied()
+        .ok_or_else(|| missing(CONFIGURE_METHOD))?;
+    if let Some(name) = methods
+        .iter()
+        .find(|name| exports.iter().any(|export| export.name == **name))
+    {
+        return Err(ErrorKind::DataConversion.message(format!(
+            "cannot mock the method `{name}`, as it is exported by the mock itself"
+        )));
+    }
+    let first_method = imported_functions + function_types.len() as u32;
+
+    let mut module = wasm_encoder::Module::new();
+    let mut code = CodeSection::new();
+    let mut remaining_bodies = 0;
+    for payload in Parser::new(0).parse_all(MOCK_WASM) {
+        match payload.map_err(invalid)? {
+            Payload::FunctionSection(_) => {
+                let mut functions = FunctionSection::new();
+                for ty in &function_types {
+                    functions.function(*ty);
+                }
+                for _ in methods {
+                    functions.function(method_type);
+                }
+                module.section(&functions);
+            }
+            Payload::ExportSection(_) => {
+                let mut section = ExportSection::new();
+                for export in exports.iter().filter(|export| export.name != HANDLE_EXPORT) {
+                    section.export(export.name, export_kind(export.kind)?, export.index);
+                }
+                for (index, name) in methods.iter().enumerate() {
+                    section.export(name, ExportKind::Func, first_method + index as u32);
+                }
+                module.section(&section);
+            }
+            Payload::CodeSectionStart { count, .. } => remaining_bodies = count,
+            Payload::CodeSectionEntry(body) => {
+                code.raw(&MOCK_WASM[body.range()]);
+                remaining_bodies -= 1;
+                if remaining_bodies == 0 {
+                    for index in 0..methods.len() {
+                        let mut method = wasm_encoder::Function::new([]);
+                        method.instruction(&Instruction::I32Const(index as i32));
+                        method.instruction(&Instruction::Call(handle));
+                        method.instruction(&Instruction::End);
+                        code.function(&method);
+                    }
+                    module.section(&code);
+                }
+            }
+            payload => {
+                if let Some((id, range)) = payload.as_section() {
+                    module.section(&RawSection {
+                        id,
+                        data: &MOCK_WASM[range],
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(module.finish())
+}
+
+fn export_kind(kind: ExternalKind) -> Result<ExportKind> {
+    Ok(match kind {
+        ExternalKind::Func => ExportKind::Func,
+        ExternalKind::Table => ExportKind::Table,
+        ExternalKind::Memory => ExportKind::Memory,
+        ExternalKind::Global => ExportKind::Global,
+        ExternalKind::Tag => ExportKind::Tag,
+        ExternalKind::FuncExact => {
+            return Err(ErrorKind::DataConversion.message("unsupported export in mock wasm"));
+        }
+    })
+}
@@ -0,0 +1,16 @@
+//! Harnesses for common contract testing workflows, such as checking that a contract upgrade
+//! migrates its state correctly, or that two versions of a contract behave the same, along
+//! with a programmable mock contract for stubbing out the contracts called by the one tested,
+//! available with the `bundled-contracts` feature.
+
+pub(crate) mod diff;
+#[cfg(feature = "bundled-contracts")]
+pub(crate) mod mock;
+pub(crate) mod replay;
+pub(crate) mod upgrade;
+
+pub use self::diff::{DiffReport, DiffTest, Divergence, DivergenceKind};
+#[cfg(feature = "bundled-contracts")]
+pub use self::mock::{MockCall, MockContract, MockMethod};
+pub use self::replay::{ReplayReport, ReplayTransactions, ReplayedCall};
+pub use self::upgrade::{UpgradeReport, UpgradeTest, ViewMismatch};
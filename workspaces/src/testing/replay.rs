@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use futures::StreamExt;
+use near_primitives::views::{ExecutionStatusView, TxExecutionStatus};
+
+use crate::network::{DEV_ACCOUNT_SEED, Sandbox};
+use crate::operations::Function;
+use crate::result::{ExecutionFinalResult, ExecutionOutcome, Result};
+use crate::rpc::patch::ImportContractTransaction;
+use crate::rpc::stream::{BlockRangeStream, account_activity_in_block};
+use crate::types::{
+    AccountActivity, BlockHeight, CryptoHash, FunctionCallDetails, KeyType, NearToken, SecretKey,
+};
+use crate::{AccessKey, Account, AccountDetailsPatch, AccountId, Contract, Network, Worker};
+
+/// Balance given to the accounts replaying calls, on top of the deposits they attach.
+const REPLAY_ACCOUNT_BALANCE: NearToken = NearToken::from_near(100);
+
+/// A [`Transaction`]-like object that replays the historical function calls made into a
+/// contract on another network, usually given from calling into
+/// [`Worker::replay_transactions`].
+///
+/// The contract is imported along with its state as of the parent of the first block in the
+/// range, and optionally upgraded to a candidate [`wasm`]. Then every receipt calling into the contract
+/// from the transactions included in the range is replayed in order, by having its
+/// predecessor sign a transaction making the same function calls with the same arguments,
+/// deposit and gas. The outcome of each replayed call is compared with the original.
+///
+/// Note that receipts made by the contract to itself, such as callbacks, are not replayed
+/// since the results of the promises they depend on are not available. The replayed calls
+/// also run at a different block height and timestamp, and see the predecessor as their
+/// signer, which contracts depending on those will behave differently with.
+///
+/// [`Transaction`]: crate::operations::Transaction
+/// [`Worker::replay_transactions`]: crate::Worker::replay_transactions
+/// [`wasm`]: ReplayTransactions::wasm
+pub struct ReplayTransactions {
+    from_network: Worker<dyn Network>,
+    into_network: Worker<Sandbox>,
+    contract_id: AccountId,
+    range: Range<BlockHeight>,
+    wasm: Option<Vec<u8>>,
+}
+
+impl ReplayTransactions {
+    pub(crate) fn new(
+        from_network: Worker<dyn Network>,
+        into_network: Worker<Sandbox>,
+        contract_id: AccountId,
+        range: Range<BlockHeight>,
+    ) -> Self {
+        Self {
+            from_network,
+            into_network,
+            contract_id,
+            range,
+            wasm: None,
+        }
+    }
+
+    /// The candidate wasm to replay the calls against. Defaults to the code the contract
+    /// had at the start of the range.
+    pub fn wasm(mut self, wasm: &[u8]) -> Self {
+        self.wasm = Some(wasm.to_vec());
+        self
+    }
+
+    /// Replay the calls, and report on how their outcomes compare to the originals.
+    /// Replayed transactions that fail to be submitted, such as by being rejected as invalid,
+    /// are recorded in the report rather than stopping the replay.
+    pub async fn transact(self) -> Result<ReplayReport> {
+        let mut blocks = BlockRangeStream::new(self.from_network.clone(), self.range.clone());
+        let mut prev_hash = None;
+        let mut activities = Vec::new();
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            // Heights can be skipped, so the parent of the first block that exists within
+            // the range is the state right before it.
+            prev_hash.get_or_insert(*block.header().prev_hash());
+            activities.extend(
+                account_activity_in_block(
+                    &self.from_network,
+                    &self.contract_id,
+                    &block,
+                    TxExecutionStatus::Final,
+                )
+                .await?
+                .into_iter()
+                .filter(|activity| {
+                    !activity.function_calls.is_empty()
+                        && activity.predecessor_id != self.contract_id
+                }),
+            );
+        }
+
+        let import = ImportContractTransaction::new(
+            &self.contract_id,
+            self.from_network.clone(),
+            self.into_network.clone(),
+        );
+        let import = match prev_hash {
+            Some(prev_hash) => import.block_hash(prev_hash),
+            None => import.block_height(self.range.start.saturating_sub(1)),
+        };
+        let contract = import.with_data().transact().await?;
+        if let Some(wasm) = &self.wasm {
+            self.into_network
+                .patch(&self.contract_id)
+                .code(wasm)
+                .transact()
+                .await?;
+        }
+
+        // Fund every predecessor with the deposits of all of its calls up front.
+        let mut deposits = HashMap::<&AccountId, u128>::new();
+        for activity in &activities {
+            *deposits.entry(&activity.predecessor_id).or_default() += activity
+                .function_calls
+                .iter()
+                .map(|call| call.deposit.as_yoctonear())
+                .sum::<u128>();
+        }
+        let sk = SecretKey::from_seed(KeyType::ED25519, DEV_ACCOUNT_SEED);
+        for (predecessor_id, deposits) in deposits {
+            self.into_network
+                .patch(predecessor_id)
+                .account(AccountDetailsPatch::default().balance(
+                    REPLAY_ACCOUNT_BALANCE.saturating_add(NearToken::from_yoctonear(deposits)),
+                ))
+                .access_key(sk.public_key(), AccessKey::full_access())
+                .transact()
+                .await?;
+        }
+
+        let mut calls = Vec::with_capacity(activities.len());
+        for activity in activities {
+            let signer = Account::from_secret_key(
+                activity.predecessor_id.clone(),
+                sk.clone(),
+                &self.into_network,
+            );
+            let mut tx = signer.batch(&self.contract_id);
+            for call in &activity.function_calls {
+                tx = tx.call(
+                    Function::new(&call.method_name)
+                        .args(call.args.clone())
+                        .deposit(call.deposit)
+                        .gas(call.gas),
+                );
+            }
+            let result = tx.transact().await;
+            calls.push(ReplayedCall::new(activity, result, &self.contract_id));
+        }
+
+        Ok(ReplayReport { contract, calls })
+    }
+}
+
+/// The outcome of replaying historical calls with [`ReplayTransactions`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ReplayReport {
+    /// The contract imported into sandbox, in its state after every call was replayed.
+    pub contract: Contract,
+    /// Every replayed call, in the order they were originally made.
+    pub calls: Vec<ReplayedCall>,
+}
+
+impl ReplayReport {
+    /// Iterate over the replayed calls whose outcome differs from the original.
+    pub fn divergent(&self) -> impl Iterator<Item = &ReplayedCall> {
+        self.calls.iter().filter(|call| !call.matches())
+    }
+}
+
+/// A single historical receipt replayed against sandbox.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ReplayedCall {
+    /// Hash of the original transaction the receipt originated from.
+    pub transaction_hash: CryptoHash,
+    /// The account that made the calls.
+    pub predecessor_id: AccountId,
+    /// The function calls made by the receipt.
+    pub function_calls: Vec<FunctionCallDetails>,
+    /// The outcome of the original receipt executed on the contract.
+    pub original: ExecutionOutcome,
+    /// The outcome of the replayed receipt executed on the contract, or `None` if the
+    /// replayed transaction failed before reaching the contract.
+    pub replayed: Option<ExecutionOutcome>,
+    /// The full result of the replayed transaction, or the error it could not be submitted
+    /// with.
+    pub result: Result<ExecutionFinalResult>,
+}
+
+impl ReplayedCall {
+    fn new(
+        activity: AccountActivity,
+        result: Result<ExecutionFinalResult>,
+        contract_id: &AccountId,
+    ) -> Self {
+        let replayed = result.as_ref().ok().and_then(|result| {
+            result
+                .receipt_outcomes()
+                .iter()
+                .find(|outcome| outcome.executor_id == *contract_id)
+                .cloned()
+        });
+        Self {
+            transaction_hash: activity.transaction_hash,
+            predecessor_id: activity.predecessor_id,
+            function_calls: activity.function_calls,
+            original: activity.outcome,
+            replayed,
+            result,
+        }
+    }
+
+    /// Returns true if the replayed call succeeded or failed the same way as the original,
+    /// returning the same value and emitting the same logs. Gas burnt is not compared.
+    pub fn matches(&self) -> bool {
+        let Some(replayed) = &self.replayed else {
+            return false;
+        };
+        let same_status = match (&self.original.status, &replayed.status) {
+            (ExecutionStatusView::SuccessValue(a), ExecutionStatusView::SuccessValue(b)) => a == b,
+            (
+                ExecutionStatusView::SuccessReceiptId(_),
+                ExecutionStatusView::SuccessReceiptId(_),
+            ) => true,
+            (ExecutionStatusView::Failure(_), ExecutionStatusView::Failure(_)) => true,
+            _ => false,
+        };
+        same_status && self.original.logs == replayed.logs
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use futures::TryStreamExt;
+
+use crate::operations::Function;
+use crate::result::{ExecutionFinalResult, Result};
+use crate::rpc::BoxFuture;
+use crate::types::Gas;
+use crate::{Contract, DevNetwork, Worker};
+
+type PopulateFn = Box<dyn FnOnce(Contract) -> BoxFuture<'static, Result<()>> + Send>;
+type DecodeFn = Box<dyn Fn(&[u8], &[u8]) -> bool + Send + Sync>;
+type CompatibleFn = Box<dyn Fn(&[u8], &[u8]) -> bool + Send + Sync>;
+
+/// Harness for testing the upgrade of a contract from one version to the next, along with the
+/// migration of its state. Running the test will:
+///
+/// 1. deploy the `v1` wasm to a new dev account.
+/// 2. run the script supplied to [`populate`] against it.
+/// 3. deploy the `v2` wasm and call its [`migrate`] function in a single batch transaction.
+/// 4. check the value of every key present before the upgrade with the [`decoder`].
+/// 5. check that every [`view`] function returns a compatible result to the one before.
+///
+/// The findings are returned in an [`UpgradeReport`].
+///
+/// ```no_run
+/// use near_primitives::borsh::BorshDeserialize;
+/// use near_workspaces::operations::Function;
+/// use near_workspaces::testing::UpgradeTest;
+/// use serde_json::json;
+///
+/// # #[derive(BorshDeserialize)]
+/// # #[borsh(crate = "near_primitives::borsh")]
+/// # struct StateV2 {}
+/// # async fn run() -> anyhow::Result<()> {
+/// let worker = near_workspaces::sandbox().await?;
+/// let report = UpgradeTest::new(&worker, &std::fs::read("v1.wasm")?, &std::fs::read("v2.wasm")?)
+///     .populate(|contract| async move {
+///         contract.call("new").transact().await?.into_result()?;
+///         Ok(())
+///     })
+///     .decoder(|key, value| key != b"STATE" || StateV2::try_from_slice(value).is_ok())
+///     .view(Function::new("get_total").args_json(json!({})))
+///     .run()
+///     .await?;
+/// assert!(report.is_success(), "{report:?}");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`populate`]: UpgradeTest::populate
+/// [`migrate`]: UpgradeTest::migrate
+/// [`decoder`]: UpgradeTest::decoder
+/// [`view`]: UpgradeTest::view
+pub struct UpgradeTest<T> {
+    worker: Worker<T>,
+    v1: Vec<u8>,
+    v2: Vec<u8>,
+    populate: Option<PopulateFn>,
+    migrate: Function,
+    decoder: Option<DecodeFn>,
+    views: Vec<(Function, CompatibleFn)>,
+}
+
+impl<T> UpgradeTest<T>
+where
+    T: DevNetwork + 'static,
+{
+    /// Create a harness upgrading a contract from the `v1` wasm to the `v2` wasm.
+    pub fn new(worker: &Worker<T>, v1: &[u8], v2: &[u8]) -> Self {
+        Self {
+            worker: worker.clone(),
+            v1: v1.to_vec(),
+            v2: v2.to_vec(),
+            populate: None,
+            migrate: Function::new("migrate"),
+            decoder: None,
+            views: Vec::new(),
+        }
+    }
+
+    /// Script to run against the `v1` contract before upgrading it, such as initializing it
+    /// and making calls to fill its state.
+    pub fn populate<F, Fut>(mut self, populate: F) -> Self
+    where
+        F: FnOnce(Contract) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.populate = Some(Box::new(move |contract| Box::pin(populate(contract))));
+        self
+    }
+
+    /// The migration function called right after deploying `v2`. Defaults to calling
+    /// `migrate` with no arguments.
+    pub fn migrate(mut self, function: Function) -> Self {
+        self.migrate = function;
+        self
+    }
+
+    /// Decoder for the state of `v2`, given every key present before the upgrade along with
+    /// its value after the upgrade. Returning false marks the key as orphaned.
+    pub fn decoder<F>(mut self, decoder: F) -> Self
+    where
+        F: Fn(&[u8], &[u8]) -> bool + Send + Sync + 'static,
+    {
+        self.decoder = Some(Box::new(decoder));
+        self
+    }
+
+    /// View function that should return the same result before and after the upgrade.
+    pub fn view(self, function: Function) -> Self {
+        self.view_with(function, |before, after| before == after)
+    }
+
+    /// View function whose results before and after the upgrade should be compatible, as
+    /// determined by `compatible` given both results.
+    pub fn view_with<F>(mut self, function: Function, compatible: F) -> Self
+    where
+        F: Fn(&[u8], &[u8]) -> bool + Send + Sync + 'static,
+    {
+        self.views.push((function, Box::new(compatible)));
+        self
+    }
+
+    /// Run the upgrade, and report on its outcome. Errors are only returned for failures
+    /// before the upgrade, such as the population script failing; failures of the migration
+    /// itself are part of the report.
+    pub async fn run(self) -> Result<UpgradeReport> {
+        let contract = self.worker.dev_deploy(&self.v1).await?;
+        if let Some(populate) = self.populate {
+            populate(contract.clone()).await?;
+        }
+
+        let state_before = view_full_state(&contract).await?;
+        let mut views = Vec::with_capacity(self.views.len());
+        for (function, compatible) in self.views {
+            let args = function.args?;
+            let before = contract
+                .view(&function.name)
+                .args(args.clone())
+                .await?
+                .result;
+            views.push((function.name, args, before, compatible));
+        }
+
+        let migration = contract
+            .batch()
+            .deploy(&self.v2)
+            .call(self.migrate)
+            .transact()
+            .await?;
+        let state_after = view_full_state(&contract).await?;
+
+        let mut removed_keys = Vec::new();
+        let mut orphaned_keys = Vec::new();
+        for key in state_before.into_keys() {
+            match state_after.get(&key) {
+                None => removed_keys.push(key),
+                Some(value) => {
+                    if let Some(decoder) = &self.decoder
+                        && !decoder(&key, value)
+                    {
+                        orphaned_keys.push(key);
+                    }
+                }
+            }
+        }
+        removed_keys.sort();
+        orphaned_keys.sort();
+
+        let mut view_mismatches = Vec::new();
+        for (function, args, before, compatible) in views {
+            let after = contract.view(&function).args(args).await;
+            match after {
+                Ok(after) if compatible(&before, &after.result) => {}
+                Ok(after) => view_mismatches.push(ViewMismatch {
+                    function,
+                    before,
+                    after: Some(after.result),
+                    error: None,
+                }),
+                Err(err) => view_mismatches.push(ViewMismatch {
+                    function,
+                    before,
+                    after: None,
+                    error: Some(err.to_string()),
+                }),
+            }
+        }
+
+        Ok(UpgradeReport {
+            migration_gas: migration.total_gas_burnt,
+            migration,
+            removed_keys,
+            orphaned_keys,
+            view_mismatches,
+            contract,
+        })
+    }
+}
+
+/// The outcome of running an [`UpgradeTest`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct UpgradeReport {
+    /// The upgraded contract, for making further assertions against.
+    pub contract: Contract,
+    /// The result of the batch transaction deploying `v2` and calling the migration.
+    pub migration: ExecutionFinalResult,
+    /// Total gas burnt by the migration.
+    pub migration_gas: Gas,
+    /// Keys present before the upgrade that were removed by the migration.
+    pub removed_keys: Vec<Vec<u8>>,
+    /// Keys present both before and after the upgrade whose value the decoder could not
+    /// make sense of.
+    pub orphaned_keys: Vec<Vec<u8>>,
+    /// View functions whose results were not compatible before and after the upgrade.
+    pub view_mismatches: Vec<ViewMismatch>,
+}
+
+impl UpgradeReport {
+    /// Returns true if the migration succeeded, no keys were orphaned, and every view
+    /// function returned a compatible result.
+    pub fn is_success(&self) -> bool {
+        self.migration.is_success()
+            && self.orphaned_keys.is_empty()
+            && self.view_mismatches.is_empty()
+    }
+}
+
+/// A view function whose results were not compatible before and after an upgrade.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ViewMismatch {
+    /// Name of the view function.
+    pub function: String,
+    /// The result before the upgrade.
+    pub before: Vec<u8>,
+    /// The result after the upgrade, or `None` if the view failed.
+    pub after: Option<Vec<u8>>,
+    /// The error the view failed with after the upgrade.
+    pub error: Option<String>,
+}
+
+/// View the entire state of `contract`, a page at a time so that large states are viewable.
+pub(crate) async fn view_full_state(contract: &Contract) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+    let pages = contract.view_state_pages().try_collect::<Vec<_>>().await?;
+    Ok(pages.into_iter().flatten().collect())
+}
@@ -1,14 +1,20 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::Hash;
 use std::path::Path;
 
+use futures::TryStreamExt;
+use near_primitives::borsh::BorshDeserialize;
 use near_primitives::types::StorageUsage;
 use near_primitives::views::AccountView;
 
 use crate::error::ErrorKind;
 use crate::rpc::query::{
-    Query, ViewAccessKey, ViewAccessKeyList, ViewAccount, ViewCode, ViewFunction, ViewState,
+    Query, QueryStateDiff, ViewAccessKey, ViewAccessKeyList, ViewAccount, ViewCode, ViewFunction,
+    ViewState,
 };
-use crate::types::{AccountId, InMemorySigner, NearToken, PublicKey, SecretKey};
+use crate::rpc::stream::StatePageStream;
+use crate::types::{AccountId, InMemorySigner, NearToken, PublicKey, SecretKey, collections};
 use crate::{BlockHeight, CryptoHash, Network, Worker};
 
 use crate::operations::{CallTransaction, CreateAccountTransaction, Transaction};
@@ -43,6 +49,17 @@ impl Account {
         Ok(Self::new(signer, worker.clone().coerce()))
     }
 
+    /// Create a new account from a credentials file written by
+    /// [`Account::store_credentials_encrypted`], decrypting it with `passphrase`.
+    pub fn from_encrypted_file(
+        path: impl AsRef<Path>,
+        passphrase: &str,
+        worker: &Worker<impl Network + 'static>,
+    ) -> Result<Self> {
+        let (id, sk) = crate::rpc::tool::read_encrypted_cred_from_file(path.as_ref(), passphrase)?;
+        Ok(Self::from_secret_key(id, SecretKey(sk), worker))
+    }
+
     /// Create an [`Account`] object from an [`AccountId`] and [`SecretKey`].
     pub fn from_secret_key(
         id: AccountId,
@@ -55,6 +72,19 @@ impl Account {
         }
     }
 
+    /// Create an [`Account`] object from an [`AccountId`] and the key derived from a BIP39
+    /// `seed_phrase` at `hd_path`, as a NEAR wallet would. Wallets use
+    /// [`DEFAULT_HD_PATH`](crate::types::DEFAULT_HD_PATH) for the key of an account.
+    pub fn from_seed_phrase(
+        id: AccountId,
+        seed_phrase: &str,
+        hd_path: &str,
+        worker: &Worker<impl Network + 'static>,
+    ) -> Result<Self> {
+        let sk = SecretKey::from_seed_phrase(seed_phrase, hd_path)?;
+        Ok(Self::from_secret_key(id, sk, worker))
+    }
+
     pub(crate) fn new(signer: InMemorySigner, worker: Worker<dyn Network>) -> Self {
         Self { signer, worker }
     }
@@ -189,6 +219,26 @@ impl Account {
         crate::rpc::tool::write_cred_to_file(&savepath, self.id(), &self.secret_key().0)
     }
 
+    /// Store the credentials of this account in the directory provided, with its secret key
+    /// encrypted by a key derived from `passphrase`. They are written to
+    /// `<account_id>.encrypted.json`, apart from the plaintext credentials written by
+    /// [`Account::store_credentials`], and loaded back with [`Account::from_encrypted_file`].
+    pub async fn store_credentials_encrypted(
+        &self,
+        save_dir: impl AsRef<Path> + Send,
+        passphrase: &str,
+    ) -> Result<()> {
+        let savepath = save_dir.as_ref();
+        std::fs::create_dir_all(&save_dir).map_err(|e| ErrorKind::Io.custom(e))?;
+        let savepath = savepath.join(format!("{}.encrypted.json", self.id()));
+        crate::rpc::tool::write_encrypted_cred_to_file(
+            &savepath,
+            self.id(),
+            &self.secret_key().0,
+            passphrase,
+        )
+    }
+
     /// Get the keys of this account. The public key can be retrieved from the secret key.
     pub fn secret_key(&self) -> &SecretKey {
         &self.signer.secret_key
@@ -290,6 +340,89 @@ impl Contract {
         self.account.worker.view_state(self.id())
     }
 
+    /// View this contract's state a page of key value pairs at a time. Similar to
+    /// [`Worker::view_state_pages`], this is able to view states that are too large for
+    /// [`Contract::view_state`].
+    pub fn view_state_pages(&self) -> StatePageStream {
+        StatePageStream::new(self.account.worker.clone(), self.id().clone())
+    }
+
+    /// View the root `STATE` key of this contract, where a contract using `near-sdk` keeps
+    /// its state struct, and deserialize it from borsh. Note that collections held by the
+    /// struct only store their prefix and length in it, so their entries need to be read with
+    /// the likes of [`Contract::read_lookup_map`].
+    pub async fn view_state_typed<T: BorshDeserialize>(&self) -> Result<T> {
+        // Keys are ordered, so the STATE key comes first if it exists.
+        let page = self
+            .view_state_pages()
+            .prefix(b"STATE")
+            .page_size(1)
+            .try_next()
+            .await?
+            .unwrap_or_default();
+        let value = page
+            .into_iter()
+            .find_map(|(key, value)| (key == b"STATE").then_some(value))
+            .ok_or_else(|| {
+                ErrorKind::DataConversion
+                    .message("contract has no state stored under the STATE key")
+            })?;
+        T::try_from_slice(&value).map_err(|e| ErrorKind::DataConversion.custom(e))
+    }
+
+    /// Read every entry of a `near-sdk` `LookupMap` created with `prefix` and the default
+    /// `Identity` hasher, such as `LookupMap::new(b"b")`. Every key in the contract state
+    /// starting with `prefix` is expected to belong to the map.
+    pub async fn read_lookup_map<K, V>(&self, prefix: &[u8]) -> Result<HashMap<K, V>>
+    where
+        K: BorshDeserialize + Eq + Hash,
+        V: BorshDeserialize,
+    {
+        let state = self.read_state_prefix(prefix).await?;
+        collections::decode_lookup_map(prefix, state)
+    }
+
+    /// Read every element of a `near_sdk::store::Vector` created with `prefix`, in order.
+    pub async fn read_vector<T: BorshDeserialize>(&self, prefix: &[u8]) -> Result<Vec<T>> {
+        let state = self.read_state_prefix(prefix).await?;
+        collections::decode_vector(prefix, state)
+    }
+
+    /// Read every entry of a `near_sdk::store::IterableMap` created with `prefix`, in the
+    /// order the map iterates over them.
+    pub async fn read_iterable_map<K, V>(&self, prefix: &[u8]) -> Result<Vec<(K, V)>>
+    where
+        K: BorshDeserialize,
+        V: BorshDeserialize,
+    {
+        let values_prefix = [prefix, b"m"].concat();
+        let state = self.read_state_prefix(&values_prefix).await?;
+        collections::decode_iterable_map(prefix, state)
+    }
+
+    /// Read every key starting with `prefix` a page at a time, so that collections larger
+    /// than the limit of a single [`Contract::view_state`] can be read.
+    async fn read_state_prefix(&self, prefix: &[u8]) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+        self.view_state_pages()
+            .prefix(prefix)
+            .try_fold(HashMap::new(), |mut state, page| async move {
+                state.extend(page);
+                Ok(state)
+            })
+            .await
+    }
+
+    /// Compute the difference in this contract's storage between the `from` and `to` block
+    /// heights, once awaited. The resulting [`StateDiff`] contains the keys that were added,
+    /// removed or modified along with their old and new values. Supply a [`prefix`] to only
+    /// diff the keys starting with it.
+    ///
+    /// [`StateDiff`]: crate::types::StateDiff
+    /// [`prefix`]: QueryStateDiff::prefix
+    pub fn state_diff(&self, from: BlockHeight, to: BlockHeight) -> QueryStateDiff<'_> {
+        QueryStateDiff::new(&self.account.worker, self.id(), from, to)
+    }
+
     /// Views the current contract's details such as balance and storage usage.
     pub fn view_account(&self) -> Query<'_, ViewAccount> {
         self.account.worker.view_account(self.id())
@@ -444,7 +577,7 @@ impl From<ContractState> for near_primitives::account::AccountContract {
 
 /// Details of an Account or Contract. This is an non-exhaustive list of items
 /// that the account stores in the blockchain state.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 pub struct AccountDetails {
     pub balance: NearToken,
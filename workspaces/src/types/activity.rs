@@ -0,0 +1,112 @@
+use near_account_id::AccountId;
+use near_primitives::views::{
+    ActionView, ExecutionOutcomeWithIdView, ReceiptEnumView, ReceiptView,
+};
+
+use crate::error::ErrorKind;
+use crate::result::{ExecutionOutcome, Result};
+use crate::types::{CryptoHash, Gas, NearToken};
+
+/// A receipt that was executed on a watched account, along with the outcome of its execution.
+/// Usually yielded from the stream returned by [`Worker::watch_account`].
+///
+/// [`Worker::watch_account`]: crate::Worker::watch_account
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AccountActivity {
+    /// The hash of the transaction that this receipt originated from.
+    pub transaction_hash: CryptoHash,
+    /// The id of the receipt executed on the account.
+    pub receipt_id: CryptoHash,
+    /// The account that sent this receipt, such as the signer of the transaction or the
+    /// contract that made a cross contract call.
+    pub predecessor_id: AccountId,
+    /// The account the receipt was executed on.
+    pub receiver_id: AccountId,
+    /// Function calls made into the account by this receipt.
+    pub function_calls: Vec<FunctionCallDetails>,
+    /// The outcome of executing this receipt.
+    pub outcome: ExecutionOutcome,
+}
+
+impl AccountActivity {
+    pub(crate) fn new(
+        transaction_hash: CryptoHash,
+        receipt: ReceiptView,
+        outcome: ExecutionOutcomeWithIdView,
+    ) -> Self {
+        let function_calls = match receipt.receipt {
+            ReceiptEnumView::Action { actions, .. } => actions
+                .into_iter()
+                .filter_map(|action| match action {
+                    ActionView::FunctionCall {
+                        method_name,
+                        args,
+                        gas,
+                        deposit,
+                    } => Some(FunctionCallDetails {
+                        method_name,
+                        args: args.into(),
+                        gas: Gas::from_gas(gas.as_gas()),
+                        deposit,
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            transaction_hash,
+            receipt_id: receipt.receipt_id.into(),
+            predecessor_id: receipt.predecessor_id,
+            receiver_id: receipt.receiver_id,
+            function_calls,
+            outcome: outcome.into(),
+        }
+    }
+
+    /// Checks whether this receipt called into the function `method_name` of the account.
+    pub fn calls(&self, method_name: &str) -> bool {
+        self.function_calls
+            .iter()
+            .any(|call| call.method_name == method_name)
+    }
+
+    /// Checks whether the execution of this receipt was a success.
+    pub fn is_success(&self) -> bool {
+        self.outcome.is_success()
+    }
+
+    /// Checks whether the execution of this receipt was a failure.
+    pub fn is_failure(&self) -> bool {
+        self.outcome.is_failure()
+    }
+}
+
+/// Details of a function call made by a receipt executed on an account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FunctionCallDetails {
+    /// Name of the function called.
+    pub method_name: String,
+    /// Raw bytes of the arguments passed into the function.
+    pub args: Vec<u8>,
+    /// Amount of gas attached to the call.
+    pub gas: Gas,
+    /// Amount of tokens deposited along with the call.
+    pub deposit: NearToken,
+}
+
+impl FunctionCallDetails {
+    /// Deserialize the arguments of this function call from JSON.
+    pub fn args_json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.args).map_err(|e| ErrorKind::DataConversion.custom(e))
+    }
+
+    /// Deserialize the arguments of this function call from borsh.
+    pub fn args_borsh<T: near_primitives::borsh::BorshDeserialize>(&self) -> Result<T> {
+        near_primitives::borsh::BorshDeserialize::try_from_slice(&self.args)
+            .map_err(|e| ErrorKind::DataConversion.custom(e))
+    }
+}
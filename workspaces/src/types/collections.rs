@@ -0,0 +1,135 @@
+//! Storage keys used by the collections of `near-sdk`, for reading or patching the individual
+//! entries of a collection in contract state without reverse-engineering its key layout. To
+//! read an entire collection instead, see [`Contract::read_lookup_map`], [`Contract::read_vector`]
+//! and [`Contract::read_iterable_map`].
+//!
+//! Every collection is created with a prefix, such as `LookupMap::new(b"b")` or one derived
+//! from a `BorshStorageKey` enum, which is the `prefix` expected by the functions here:
+//!
+//! ```
+//! use near_workspaces::types::collections;
+//!
+//! # fn patch(worker: &near_workspaces::Worker<near_workspaces::network::Sandbox>, contract_id: &near_workspaces::AccountId) -> near_workspaces::Result<()> {
+//! let alice: near_workspaces::AccountId = "alice.near".parse().unwrap();
+//! // Patch `balances[alice] = 100` for a `balances: LookupMap<AccountId, u128>` created
+//! // with the prefix `b"b"`.
+//! let patch = worker
+//!     .patch(contract_id)
+//!     .state_borsh(&collections::lookup_map_key(b"b", &alice)?, &100u128);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Contract::read_lookup_map`]: crate::Contract::read_lookup_map
+//! [`Contract::read_vector`]: crate::Contract::read_vector
+//! [`Contract::read_iterable_map`]: crate::Contract::read_iterable_map
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use near_primitives::borsh::{self, BorshDeserialize, BorshSerialize};
+use sha2::Digest;
+
+use crate::error::ErrorKind;
+use crate::result::Result;
+
+/// Key of the value stored for `key` in a `LookupMap` from `near_sdk::collections`, or from
+/// `near_sdk::store` using the default `Identity` hasher. This is also the key of `key` in a
+/// `LookupSet` of either module.
+pub fn lookup_map_key<K: BorshSerialize + ?Sized>(prefix: &[u8], key: &K) -> Result<Vec<u8>> {
+    let mut storage_key = prefix.to_vec();
+    borsh::to_writer(&mut storage_key, key).map_err(|e| ErrorKind::DataConversion.custom(e))?;
+    Ok(storage_key)
+}
+
+/// Key of the value stored for `key` in a `LookupMap` or `LookupSet` from `near_sdk::store`
+/// using the `Sha256` hasher, which hashes the prefix along with the key.
+pub fn lookup_map_key_sha256<K: BorshSerialize + ?Sized>(
+    prefix: &[u8],
+    key: &K,
+) -> Result<Vec<u8>> {
+    let storage_key = lookup_map_key(prefix, key)?;
+    Ok(sha2::Sha256::digest(storage_key).to_vec())
+}
+
+/// Key of the element at `index` in a `Vector` from `near_sdk::store`. Note that the length
+/// of the vector is stored along with the struct containing it rather than under its prefix.
+pub fn vector_index_key(prefix: &[u8], index: u32) -> Vec<u8> {
+    [prefix, &index.to_le_bytes()].concat()
+}
+
+/// Key of the element at `index` in a `Vector` from `near_sdk::collections`, which indexes
+/// its elements by a `u64` instead.
+pub fn legacy_vector_index_key(prefix: &[u8], index: u64) -> Vec<u8> {
+    [prefix, &index.to_le_bytes()].concat()
+}
+
+/// Decode the entries of a `LookupMap` with the `Identity` hasher from the contract `state`
+/// viewed with `prefix`.
+pub(crate) fn decode_lookup_map<K, V>(
+    prefix: &[u8],
+    state: HashMap<Vec<u8>, Vec<u8>>,
+) -> Result<HashMap<K, V>>
+where
+    K: BorshDeserialize + Eq + Hash,
+    V: BorshDeserialize,
+{
+    state
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix(prefix)?.to_vec(), value)))
+        .map(|(key, value)| Ok((decode(&key)?, decode(&value)?)))
+        .collect()
+}
+
+/// Decode the elements of a `Vector` from `near_sdk::store` from the contract `state` viewed
+/// with `prefix`, ordered by their index.
+pub(crate) fn decode_vector<T: BorshDeserialize>(
+    prefix: &[u8],
+    state: HashMap<Vec<u8>, Vec<u8>>,
+) -> Result<Vec<T>> {
+    let mut elements = state
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let index = key.strip_prefix(prefix)?.try_into().ok()?;
+            Some((u32::from_le_bytes(index), value))
+        })
+        .collect::<Vec<_>>();
+    elements.sort_by_key(|(index, _)| *index);
+    elements
+        .into_iter()
+        .map(|(_, value)| decode(&value))
+        .collect()
+}
+
+/// Decode the entries of an `IterableMap` from `near_sdk::store` from the contract `state`
+/// viewed with `prefix`, ordered by their insertion. The map stores the index of each key
+/// alongside its value, under the prefix followed by `m`.
+pub(crate) fn decode_iterable_map<K, V>(
+    prefix: &[u8],
+    state: HashMap<Vec<u8>, Vec<u8>>,
+) -> Result<Vec<(K, V)>>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+    let values_prefix = [prefix, b"m"].concat();
+    let mut entries = state
+        .into_iter()
+        .filter_map(|(key, value)| {
+            Some((key.strip_prefix(values_prefix.as_slice())?.to_vec(), value))
+        })
+        .map(|(key, value)| {
+            let (value, index): (V, u32) = decode(&value)?;
+            Ok((index, decode(&key)?, value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by_key(|(index, _, _)| *index);
+    Ok(entries
+        .into_iter()
+        .map(|(_, key, value)| (key, value))
+        .collect())
+}
+
+fn decode<T: BorshDeserialize>(bytes: &[u8]) -> Result<T> {
+    T::try_from_slice(bytes).map_err(|e| ErrorKind::DataConversion.custom(e))
+}
@@ -0,0 +1,217 @@
+//! Client for fungible token contracts implementing the [NEP-141] core standard, along with
+//! [NEP-145] storage management and [NEP-148] metadata, such as the reference fungible token
+//! bundled as `FUNGIBLE_TOKEN_WASM` and deployed with `Worker::deploy_ft` with the
+//! `bundled-contracts` feature.
+//!
+//! [NEP-141]: https://nomicon.io/Standards/Tokens/FungibleToken/Core
+//! [NEP-145]: https://nomicon.io/Standards/StorageManagement
+//! [NEP-148]: https://nomicon.io/Standards/Tokens/FungibleToken/Metadata
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::result::{ExecutionFinalResult, Result};
+use crate::types::{Gas, NearToken, ONE_YOCTO, parse_u128};
+use crate::{Account, AccountId, Contract};
+
+/// The reference fungible token contract from `near-sdk`, initialized with
+/// `new(owner_id, total_supply, metadata)` minting the total supply to the owner.
+#[cfg(feature = "bundled-contracts")]
+pub const FUNGIBLE_TOKEN_WASM: &[u8] = include_bytes!("../../res/fungible_token.wasm");
+
+/// Gas attached to `ft_transfer_call`, covering the call to the receiver along with the
+/// callback resolving the transfer.
+const FT_TRANSFER_CALL_GAS: Gas = Gas::from_tgas(100);
+
+/// Metadata of a fungible token, as described by [NEP-148].
+///
+/// [NEP-148]: https://nomicon.io/Standards/Tokens/FungibleToken/Metadata
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FungibleTokenMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub reference: Option<String>,
+    /// Base64 encoded sha256 hash of the JSON file pointed to by `reference`.
+    pub reference_hash: Option<String>,
+    pub decimals: u8,
+}
+
+impl FungibleTokenMetadata {
+    /// Metadata of the current `ft-1.0.0` spec, with no icon or reference.
+    pub fn new(name: &str, symbol: &str, decimals: u8) -> Self {
+        Self {
+            spec: "ft-1.0.0".into(),
+            name: name.into(),
+            symbol: symbol.into(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals,
+        }
+    }
+}
+
+/// Storage balance of an account registered with a contract, as described by [NEP-145].
+///
+/// [NEP-145]: https://nomicon.io/Standards/StorageManagement
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageBalance {
+    pub total: NearToken,
+    pub available: NearToken,
+}
+
+/// The minimum and maximum storage balance an account can have with a contract, as described
+/// by [NEP-145].
+///
+/// [NEP-145]: https://nomicon.io/Standards/StorageManagement
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageBalanceBounds {
+    pub min: NearToken,
+    pub max: Option<NearToken>,
+}
+
+/// A fungible token contract, wrapping its [`Contract`] with typed calls to the methods of
+/// the standard. The one yoctoNEAR deposit and the gas required by each call are attached
+/// automatically.
+///
+/// Calls are signed by the given account and return their [`ExecutionFinalResult`], so that
+/// failures such as an insufficient balance can be asserted on. Views return their value.
+#[derive(Clone, Debug)]
+pub struct FungibleToken {
+    contract: Contract,
+}
+
+impl FungibleToken {
+    /// Wrap a `contract` implementing the fungible token standard.
+    pub fn new(contract: Contract) -> Self {
+        Self { contract }
+    }
+
+    /// Account ID of the token contract.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`], for calling methods outside of the standard.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Transfer `amount` tokens from `sender` to `receiver_id`.
+    pub async fn ft_transfer(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) -> Result<ExecutionFinalResult> {
+        sender
+            .call(self.id(), "ft_transfer")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amount": amount.to_string(),
+                "memo": memo,
+            }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+    }
+
+    /// Transfer `amount` tokens from `sender` to the contract `receiver_id`, and call its
+    /// `ft_on_transfer` with `msg`. The transaction returns the amount of tokens the receiver
+    /// ended up using, as a JSON string.
+    pub async fn ft_transfer_call(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+        msg: &str,
+    ) -> Result<ExecutionFinalResult> {
+        sender
+            .call(self.id(), "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amount": amount.to_string(),
+                "msg": msg,
+            }))
+            .deposit(ONE_YOCTO)
+            .gas(FT_TRANSFER_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Balance of tokens held by `account_id`.
+    pub async fn ft_balance_of(&self, account_id: &AccountId) -> Result<u128> {
+        let balance: String = self
+            .contract
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json()?;
+        parse_u128(&balance)
+    }
+
+    /// Total supply of tokens.
+    pub async fn ft_total_supply(&self) -> Result<u128> {
+        let supply: String = self.contract.view("ft_total_supply").await?.json()?;
+        parse_u128(&supply)
+    }
+
+    /// Metadata of the token.
+    pub async fn ft_metadata(&self) -> Result<FungibleTokenMetadata> {
+        self.contract.view("ft_metadata").await?.json()
+    }
+
+    /// Register `account_id` with the contract, paid for by `payer`. The minimum storage
+    /// balance required by the contract is attached, which only registers the account.
+    pub async fn storage_deposit(
+        &self,
+        payer: &Account,
+        account_id: &AccountId,
+    ) -> Result<ExecutionFinalResult> {
+        let bounds = self.storage_balance_bounds().await?;
+        payer
+            .call(self.id(), "storage_deposit")
+            .args_json(json!({
+                "account_id": account_id,
+                "registration_only": true,
+            }))
+            .deposit(bounds.min)
+            .transact()
+            .await
+    }
+
+    /// Storage balance of `account_id`, or `None` if it is not registered.
+    pub async fn storage_balance_of(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<StorageBalance>> {
+        self.contract
+            .view("storage_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json()
+    }
+
+    /// The storage balance an account needs to have to be registered with the contract.
+    pub async fn storage_balance_bounds(&self) -> Result<StorageBalanceBounds> {
+        self.contract.view("storage_balance_bounds").await?.json()
+    }
+
+    /// Unregister `account`, refunding its storage balance. Unless `force` is set, this fails
+    /// if the account still holds tokens; otherwise they are burnt.
+    pub async fn storage_unregister(
+        &self,
+        account: &Account,
+        force: bool,
+    ) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "storage_unregister")
+            .args_json(json!({ "force": force }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+    }
+}
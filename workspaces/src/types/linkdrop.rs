@@ -0,0 +1,131 @@
+//! Client for the linkdrop contract running on the `near` top level account, deployed to a
+//! sandbox built with `with_standard_contracts()` and returned by `Worker::linkdrop`, both
+//! available with the `bundled-contracts` feature.
+//!
+//! As on mainnet, `.near` names are created by calling `create_account` on `near`, which
+//! creates the account with the attached deposit and refunds it if the account could not be
+//! created, such as when it already exists.
+
+use serde_json::json;
+
+use crate::result::{ExecutionFinalResult, Result};
+use crate::types::{Gas, NearToken, PublicKey, SecretKey};
+use crate::{Account, AccountId, Contract};
+
+/// Gas attached to calls creating an account, covering the callback checking whether it
+/// was created.
+const LINKDROP_CREATE_ACCOUNT_GAS: Gas = Gas::from_tgas(50);
+
+/// A linkdrop contract, wrapping its [`Contract`] with typed calls to its methods. The gas
+/// required by each call is attached automatically.
+///
+/// Calls return their [`ExecutionFinalResult`]. Note that creating an account which already
+/// exists does not fail the transaction: the deposit is refunded and the call returns `false`.
+#[derive(Clone, Debug)]
+pub struct Linkdrop {
+    contract: Contract,
+}
+
+impl Linkdrop {
+    /// Wrap a `contract` running the linkdrop contract.
+    pub fn new(contract: Contract) -> Self {
+        Self { contract }
+    }
+
+    /// Account ID of the linkdrop contract.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`], for calling methods not covered here.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Create `new_account_id` with `new_public_key` as its full access key, funded with
+    /// `amount` paid by `payer`. The transaction returns whether the account was created.
+    pub async fn create_account(
+        &self,
+        payer: &Account,
+        new_account_id: &AccountId,
+        new_public_key: &PublicKey,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        payer
+            .call(self.id(), "create_account")
+            .args_json(json!({
+                "new_account_id": new_account_id,
+                "new_public_key": new_public_key,
+            }))
+            .deposit(amount)
+            .gas(LINKDROP_CREATE_ACCOUNT_GAS)
+            .transact()
+            .await
+    }
+
+    /// Drop `amount` from `sender` to `public_key`, which becomes allowed to claim it with
+    /// [`Linkdrop::claim`] or [`Linkdrop::create_account_and_claim`]. Part of the amount is
+    /// kept as the allowance of the key to pay for claiming.
+    pub async fn send(
+        &self,
+        sender: &Account,
+        public_key: &PublicKey,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        sender
+            .call(self.id(), "send")
+            .args_json(json!({ "public_key": public_key }))
+            .deposit(amount)
+            .transact()
+            .await
+    }
+
+    /// Claim the NEAR dropped to the key of `secret_key` into the existing `account_id`.
+    pub async fn claim(
+        &self,
+        secret_key: &SecretKey,
+        account_id: &AccountId,
+    ) -> Result<ExecutionFinalResult> {
+        self.signed_by(secret_key)
+            .call(self.id(), "claim")
+            .args_json(json!({ "account_id": account_id }))
+            .transact()
+            .await
+    }
+
+    /// Claim the NEAR dropped to the key of `secret_key` into the new account
+    /// `new_account_id`, with `new_public_key` as its full access key.
+    pub async fn create_account_and_claim(
+        &self,
+        secret_key: &SecretKey,
+        new_account_id: &AccountId,
+        new_public_key: &PublicKey,
+    ) -> Result<ExecutionFinalResult> {
+        self.signed_by(secret_key)
+            .call(self.id(), "create_account_and_claim")
+            .args_json(json!({
+                "new_account_id": new_account_id,
+                "new_public_key": new_public_key,
+            }))
+            .gas(LINKDROP_CREATE_ACCOUNT_GAS)
+            .transact()
+            .await
+    }
+
+    /// The NEAR dropped to `public_key` and not claimed yet.
+    pub async fn get_key_balance(&self, public_key: &PublicKey) -> Result<NearToken> {
+        self.contract
+            .view("get_key_balance")
+            .args_json(json!({ "key": public_key }))
+            .await?
+            .json()
+    }
+
+    /// The linkdrop account signing with a key added by [`Linkdrop::send`], which may only
+    /// call the claiming methods.
+    fn signed_by(&self, secret_key: &SecretKey) -> Account {
+        let mut account = self.contract.as_account().clone();
+        account.set_secret_key(secret_key.clone());
+        account
+    }
+}
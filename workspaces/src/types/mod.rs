@@ -5,12 +5,22 @@
 //! changes on the library consumer side is needed. Just keep using these types defined here as-is.
 
 pub(crate) mod account;
+pub(crate) mod activity;
 pub(crate) mod block;
 pub(crate) mod chunk;
 pub(crate) mod gas_meter;
+pub(crate) mod state_change;
+
+pub mod collections;
+pub mod ft;
+pub mod linkdrop;
+pub mod multisig;
+pub mod nft;
+pub mod staking;
 
 #[cfg(feature = "interop_sdk")]
 mod sdk;
+mod seed_phrase;
 
 use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display};
@@ -29,8 +39,11 @@ use crate::error::{Error, ErrorKind};
 use crate::result::Result;
 
 pub use self::account::{AccountDetails, AccountDetailsPatch};
+pub use self::activity::{AccountActivity, FunctionCallDetails};
 pub use self::chunk::{Chunk, ChunkHeader};
 pub use self::gas_meter::GasMeter;
+pub use self::seed_phrase::DEFAULT_HD_PATH;
+pub use self::state_change::{StateChange, StateChanges, StateDiff, ValueChange};
 
 /// Nonce is a unit used to determine the order of transactions in the pool.
 pub type Nonce = u64;
@@ -47,6 +60,18 @@ pub type BlockHeight = u64;
 /// Shard index, from 0 to NUM_SHARDS - 1.
 pub type ShardId = u64;
 
+/// Deposit required by the token standards for calls that transfer tokens or approvals, so
+/// that they can only be made with a full access key.
+pub(crate) const ONE_YOCTO: NearToken = NearToken::from_yoctonear(1);
+
+/// Parse a `u128` given as a JSON string, such as an amount of tokens, since JSON numbers
+/// cannot hold one.
+pub(crate) fn parse_u128(value: &str) -> Result<u128> {
+    value
+        .parse()
+        .map_err(|e| ErrorKind::DataConversion.custom(e))
+}
+
 fn from_base58(s: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
     bs58::decode(s).into_vec().map_err(|err| err.into())
 }
@@ -183,6 +208,35 @@ impl PublicKey {
     pub fn key_data(&self) -> &[u8] {
         self.0.key_data()
     }
+
+    /// Get the implicit account ID controlled by this key, which is the hex encoding of an
+    /// ED25519 public key. This will return an error for any other [`KeyType`].
+    pub fn implicit_account_id(&self) -> Result<AccountId> {
+        match &self.0 {
+            near_crypto::PublicKey::ED25519(pk) => {
+                Ok(near_primitives::utils::derive_near_implicit_account_id(pk))
+            }
+            _ => Err(ErrorKind::DataConversion.message(format!(
+                "implicit account IDs are derived from ED25519 keys, not {}",
+                self.key_type()
+            ))),
+        }
+    }
+
+    /// Get the ETH-implicit account ID controlled by this key, which is `0x` followed by the
+    /// last 20 bytes of the keccak256 hash of a SECP256K1 public key, as Ethereum addresses
+    /// are. This will return an error for any other [`KeyType`].
+    pub fn eth_implicit_account_id(&self) -> Result<AccountId> {
+        match &self.0 {
+            near_crypto::PublicKey::SECP256K1(pk) => {
+                Ok(near_primitives::utils::derive_eth_implicit_account_id(pk))
+            }
+            _ => Err(ErrorKind::DataConversion.message(format!(
+                "ETH-implicit account IDs are derived from SECP256K1 keys, not {}",
+                self.key_type()
+            ))),
+        }
+    }
 }
 
 impl Display for PublicKey {
@@ -191,6 +245,60 @@ impl Display for PublicKey {
     }
 }
 
+/// Handle of a [`PublicKey`] as stored on chain, such as in the list of access keys of an
+/// account. ED25519 and SECP256K1 keys are stored in full, while post-quantum ML-DSA-65 keys
+/// are only stored as the SHA3-256 hash of the public key, from which it cannot be recovered.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct PublicKeyHandle(pub(crate) near_crypto::PublicKeyHandle);
+
+impl PublicKeyHandle {
+    /// Get the [`KeyType`] of the key this handle refers to.
+    pub fn key_type(&self) -> KeyType {
+        KeyType::from_near_keytype(self.0.key_type())
+    }
+
+    /// Get the full [`PublicKey`] this handle refers to. This will return `None` for ML-DSA-65
+    /// keys, whose handle only holds the hash of the public key.
+    pub fn full_public_key(&self) -> Option<PublicKey> {
+        self.0.full_pubkey().map(PublicKey)
+    }
+
+    /// Whether this handle refers to `public_key`, which is how an ML-DSA-65 key can be found
+    /// in a list of access keys.
+    pub fn matches(&self, public_key: &PublicKey) -> bool {
+        *self == Self::from(public_key)
+    }
+}
+
+impl From<&PublicKey> for PublicKeyHandle {
+    fn from(public_key: &PublicKey) -> Self {
+        Self((&public_key.0).into())
+    }
+}
+
+impl From<PublicKey> for PublicKeyHandle {
+    fn from(public_key: PublicKey) -> Self {
+        Self(public_key.0.into())
+    }
+}
+
+impl Display for PublicKeyHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for PublicKeyHandle {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let handle = near_crypto::PublicKeyHandle::from_str(value)
+            .map_err(|e| ErrorKind::DataConversion.custom(e))?;
+
+        Ok(Self(handle))
+    }
+}
+
 impl FromStr for PublicKey {
     type Err = Error;
 
@@ -237,7 +345,10 @@ impl BorshDeserialize for PublicKey {
 /// Usually created along with a [`PublicKey`]
 /// to form a keypair associated to the account. To generate a new keypair, use
 /// one of the creation methods found here, such as [`SecretKey::from_seed`]
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+///
+/// The key is redacted from its [`Debug`] output, so that it doesn't end up in logs. Use
+/// [`SecretKey::expose_secret`] to get its string form.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SecretKey(pub(crate) near_crypto::SecretKey);
 
 impl SecretKey {
@@ -251,6 +362,12 @@ impl SecretKey {
         PublicKey(self.0.public_key())
     }
 
+    /// Get the string form of this secret key, such as `ed25519:...`, which can be parsed
+    /// back with [`FromStr`]. Take care not to log it.
+    pub fn expose_secret(&self) -> String {
+        self.0.to_string()
+    }
+
     /// Generate a new secret key provided the [`KeyType`] and seed.
     pub fn from_seed(key_type: KeyType, seed: &str) -> Self {
         let key_type = key_type.into_near_keytype();
@@ -263,11 +380,31 @@ impl SecretKey {
         let key_type = key_type.into_near_keytype();
         Self(near_crypto::SecretKey::from_random(key_type))
     }
+
+    /// Derive the ED25519 secret key at `hd_path` from a BIP39 english `seed_phrase`,
+    /// following SLIP-10. This is the key NEAR wallets derive, which use [`DEFAULT_HD_PATH`]
+    /// for the key of an account. Unlike [`SecretKey::from_seed`], this is wallet compatible.
+    pub fn from_seed_phrase(seed_phrase: &str, hd_path: &str) -> Result<Self> {
+        let seed = seed_phrase::derive_ed25519(seed_phrase, hd_path)?;
+        let keypair = ed25519_dalek::SigningKey::from_bytes(&seed).to_keypair_bytes();
+        Ok(Self(near_crypto::SecretKey::ED25519(
+            near_crypto::ED25519SecretKey(keypair),
+        )))
+    }
+
+    /// Generate a new 12 word seed phrase using OS provided entropy, along with the secret
+    /// key derived from it at [`DEFAULT_HD_PATH`].
+    pub fn generate_seed_phrase() -> (String, Self) {
+        let phrase = seed_phrase::generate();
+        let sk = Self::from_seed_phrase(&phrase, DEFAULT_HD_PATH)
+            .expect("generated seed phrases and the default HD path are valid");
+        (phrase, sk)
+    }
 }
 
-impl Display for SecretKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.0)
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey({}:<redacted>)", self.key_type())
     }
 }
 
@@ -288,6 +425,15 @@ pub struct InMemorySigner {
     pub(crate) secret_key: SecretKey,
 }
 
+impl Debug for InMemorySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InMemorySigner")
+            .field("account_id", &self.account_id)
+            .field("public_key", &self.secret_key.public_key())
+            .finish()
+    }
+}
+
 impl InMemorySigner {
     pub fn from_secret_key(account_id: AccountId, secret_key: SecretKey) -> Self {
         Self {
@@ -404,7 +550,7 @@ impl From<near_primitives::hash::CryptoHash> for CryptoHash {
 /// is identified by a unique (within the account) public key. One account may have large number of
 /// access keys. Access keys allow to act on behalf of the account by restricting transactions
 /// that can be issued.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AccessKey {
     /// The nonce for this access key.
     /// NOTE: In some cases the access key needs to be recreated. If the new access key reuses the
@@ -443,36 +589,28 @@ impl AccessKey {
 /// Similar to an [`AccessKey`], but also has the [`PublicKey`] associated with it.
 #[derive(Clone, Debug)]
 pub struct AccessKeyInfo {
-    pub public_key: PublicKey,
+    /// Handle of the public key, as stored on chain. This is always available, even for
+    /// ML-DSA-65 keys, and can be compared to a public key with [`PublicKeyHandle::matches`].
+    pub public_key_handle: PublicKeyHandle,
+    /// The full public key, or `None` for ML-DSA-65 keys as only their hash is stored on
+    /// chain.
+    pub public_key: Option<PublicKey>,
     pub access_key: AccessKey,
 }
 
-impl TryFrom<near_primitives::views::AccessKeyInfoView> for AccessKeyInfo {
-    type Error = crate::error::Error;
-
-    fn try_from(
-        view: near_primitives::views::AccessKeyInfoView,
-    ) -> std::result::Result<Self, Self::Error> {
-        // Since nearcore 2.13 the view holds a `PublicKeyHandle`; `full_pubkey`
-        // recovers the underlying key for ED25519/SECP256K1 and yields `None`
-        // for post-quantum ML-DSA-65 keys, whose full 1952-byte pubkey is not
-        // recoverable from the 32-byte on-trie hash the handle carries. Such
-        // access keys can be created and signed with, but cannot be listed via
-        // this view, so we return an error rather than panicking.
-        let public_key = view.public_key.full_pubkey().ok_or_else(|| {
-            ErrorKind::DataConversion.custom(
-                "ML-DSA-65 access key cannot be listed: its full public key is not recoverable from the on-trie hash",
-            )
-        })?;
-        Ok(Self {
-            public_key: PublicKey(public_key),
+impl From<near_primitives::views::AccessKeyInfoView> for AccessKeyInfo {
+    fn from(view: near_primitives::views::AccessKeyInfoView) -> Self {
+        let public_key_handle = PublicKeyHandle(view.public_key);
+        Self {
+            public_key: public_key_handle.full_public_key(),
+            public_key_handle,
             access_key: view.access_key.into(),
-        })
+        }
     }
 }
 
 /// Defines permissions for AccessKey
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AccessKeyPermission {
     FunctionCall(FunctionCallPermission),
 
@@ -486,7 +624,7 @@ pub enum AccessKeyPermission {
 /// The permission can limit the allowed balance to be spent on the prepaid gas.
 /// It also restrict the account ID of the receiver for this function call.
 /// It also can restrict the method name for the allowed function calls.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FunctionCallPermission {
     /// Allowance is a balance limit to use by this access key to pay for function call gas and
     /// transaction fees. When this access key is used, both account balance and the allowance is
@@ -0,0 +1,77 @@
+//! Client for the multisig factory running on the `multisig.near` account, deployed to a
+//! sandbox built with `with_standard_contracts()` and returned by `Worker::multisig_factory`,
+//! both available with the `bundled-contracts` feature.
+//!
+//! The factory deploys a multisig contract to a new sub-account of `multisig.near` with
+//! `create`, refunding the attached deposit if it could not be created, such as when it
+//! already exists. Requests are then added and confirmed on the multisig contract itself
+//! through its [`Contract`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::result::{ExecutionFinalResult, Result};
+use crate::types::{Gas, NearToken, PublicKey};
+use crate::{Account, AccountId, Contract};
+
+/// Gas attached to calls creating a multisig contract, covering its initialization and the
+/// callback checking whether it was created.
+const MULTISIG_CREATE_GAS: Gas = Gas::from_tgas(100);
+
+/// A member of a multisig contract, allowed to add and confirm its requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MultisigMember {
+    /// A key added to the multisig account itself, allowed to call the multisig methods.
+    AccessKey { public_key: PublicKey },
+    /// An account calling the multisig contract.
+    Account { account_id: AccountId },
+}
+
+/// A multisig factory contract, wrapping its [`Contract`] with typed calls to its methods.
+/// The gas required by each call is attached automatically.
+#[derive(Clone, Debug)]
+pub struct MultisigFactory {
+    contract: Contract,
+}
+
+impl MultisigFactory {
+    /// Wrap a `contract` running the multisig factory contract.
+    pub fn new(contract: Contract) -> Self {
+        Self { contract }
+    }
+
+    /// Account ID of the multisig factory contract.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`], for calling methods not covered here.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Create the multisig contract `<name>.<factory>` with `members`, `num_confirmations` of
+    /// which must confirm each request, funded with `amount` paid by `payer`. The transaction
+    /// returns whether the contract was created.
+    pub async fn create(
+        &self,
+        payer: &Account,
+        name: &str,
+        members: &[MultisigMember],
+        num_confirmations: u32,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        payer
+            .call(self.id(), "create")
+            .args_json(json!({
+                "name": name,
+                "members": members,
+                "num_confirmations": num_confirmations,
+            }))
+            .deposit(amount)
+            .gas(MULTISIG_CREATE_GAS)
+            .transact()
+            .await
+    }
+}
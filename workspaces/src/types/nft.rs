@@ -0,0 +1,387 @@
+//! Client for non-fungible token contracts implementing the [NEP-171] core standard, along
+//! with [NEP-177] metadata, [NEP-178] approval management and [NEP-181] enumeration, such as
+//! the reference non-fungible token bundled as `NON_FUNGIBLE_TOKEN_WASM` and deployed with
+//! `Worker::deploy_nft` with the `bundled-contracts` feature. The events emitted by such contracts are parsed with [`nft_events`].
+//!
+//! [NEP-171]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Core
+//! [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+//! [NEP-178]: https://nomicon.io/Standards/Tokens/NonFungibleToken/ApprovalManagement
+//! [NEP-181]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Enumeration
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::ErrorKind;
+use crate::result::{ExecutionFinalResult, Result};
+use crate::types::{Gas, NearToken, ONE_YOCTO, parse_u128};
+use crate::{Account, AccountId, Contract};
+
+/// The reference non-fungible token contract from `near-sdk`, initialized with
+/// `new(owner_id, metadata)`. Only its owner can mint tokens.
+#[cfg(feature = "bundled-contracts")]
+pub const NON_FUNGIBLE_TOKEN_WASM: &[u8] = include_bytes!("../../res/non_fungible_token.wasm");
+
+/// Deposit attached to calls storing a token or an approval, with whatever is left after
+/// paying for the storage being refunded.
+const NFT_STORAGE_DEPOSIT: NearToken = NearToken::from_millinear(10);
+
+/// Gas attached to calls that call into another contract, covering that call along with
+/// any callback resolving it.
+const NFT_CALL_GAS: Gas = Gas::from_tgas(100);
+
+/// Prefix of the logs holding events, as described by [NEP-297].
+///
+/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
+const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+
+/// Metadata of a non-fungible token contract, as described by [NEP-177].
+///
+/// [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftContractMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    /// Base64 encoded sha256 hash of the JSON file pointed to by `reference`.
+    pub reference_hash: Option<String>,
+}
+
+impl NftContractMetadata {
+    /// Metadata of the current `nft-1.0.0` spec, with no icon or links.
+    pub fn new(name: &str, symbol: &str) -> Self {
+        Self {
+            spec: "nft-1.0.0".into(),
+            name: name.into(),
+            symbol: symbol.into(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+/// Metadata of a single token, as described by [NEP-177]. Every field is optional.
+///
+/// [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    /// Base64 encoded sha256 hash of the content pointed to by `media`.
+    pub media_hash: Option<String>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    /// Base64 encoded sha256 hash of the JSON file pointed to by `reference`.
+    pub reference_hash: Option<String>,
+}
+
+/// A token as returned by the views of the standard.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    /// Metadata of the token, if the contract implements [NEP-177].
+    ///
+    /// [NEP-177]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
+    #[serde(default)]
+    pub metadata: Option<TokenMetadata>,
+    /// Accounts approved to transfer the token along with their approval ID, if the contract
+    /// implements [NEP-178].
+    ///
+    /// [NEP-178]: https://nomicon.io/Standards/Tokens/NonFungibleToken/ApprovalManagement
+    #[serde(default)]
+    pub approved_account_ids: Option<HashMap<AccountId, u64>>,
+}
+
+/// An event emitted by a non-fungible token contract, as described by [NEP-171].
+///
+/// [NEP-171]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Core#events
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum NftEvent {
+    NftMint(Vec<NftMintData>),
+    NftTransfer(Vec<NftTransferData>),
+    NftBurn(Vec<NftBurnData>),
+    ContractMetadataUpdate(Vec<NftContractMetadataUpdateData>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftMintData {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftTransferData {
+    /// The approved account that made the transfer on behalf of the owner, if any.
+    pub authorized_id: Option<AccountId>,
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftBurnData {
+    /// The approved account that burnt the tokens on behalf of the owner, if any.
+    pub authorized_id: Option<AccountId>,
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftContractMetadataUpdateData {
+    pub memo: Option<String>,
+}
+
+/// Every [NEP-171] event emitted by the receipts of `result`, in the order they were
+/// emitted. Logs that are not such events are skipped, while events that do not follow the
+/// standard are returned as an error.
+///
+/// [NEP-171]: https://nomicon.io/Standards/Tokens/NonFungibleToken/Core#events
+pub fn nft_events(result: &ExecutionFinalResult) -> Result<Vec<NftEvent>> {
+    #[derive(Deserialize)]
+    struct Event {
+        standard: String,
+        #[serde(flatten)]
+        event: serde_json::Value,
+    }
+
+    result
+        .logs()
+        .into_iter()
+        .filter_map(|log| log.strip_prefix(EVENT_LOG_PREFIX))
+        .filter_map(|event| match serde_json::from_str::<Event>(event) {
+            Ok(event) if event.standard == "nep171" => Some(Ok(event.event)),
+            Ok(_) => None,
+            Err(e) => Some(Err(ErrorKind::DataConversion.custom(e))),
+        })
+        .map(|event| {
+            serde_json::from_value(event?).map_err(|e| ErrorKind::DataConversion.custom(e))
+        })
+        .collect()
+}
+
+/// A non-fungible token contract, wrapping its [`Contract`] with typed calls to the methods
+/// of the standard. The deposits and the gas required by each call are attached
+/// automatically.
+///
+/// Calls are signed by the given account and return their [`ExecutionFinalResult`], so that
+/// failures such as transferring a token not owned can be asserted on, and events can be
+/// parsed with [`nft_events`]. Views return their value.
+#[derive(Clone, Debug)]
+pub struct NonFungibleToken {
+    contract: Contract,
+}
+
+impl NonFungibleToken {
+    /// Wrap a `contract` implementing the non-fungible token standard.
+    pub fn new(contract: Contract) -> Self {
+        Self { contract }
+    }
+
+    /// Account ID of the token contract.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`], for calling methods outside of the standard.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Mint the token `token_id` to `owner_id`, as done by the bundled reference contract.
+    /// Only the owner of that contract can mint. Minting is not part of the standard, so
+    /// other contracts may not support it.
+    pub async fn nft_mint(
+        &self,
+        minter: &Account,
+        token_id: &str,
+        owner_id: &AccountId,
+        metadata: TokenMetadata,
+    ) -> Result<ExecutionFinalResult> {
+        minter
+            .call(self.id(), "nft_mint")
+            .args_json(json!({
+                "token_id": token_id,
+                "token_owner_id": owner_id,
+                "token_metadata": metadata,
+            }))
+            .deposit(NFT_STORAGE_DEPOSIT)
+            .transact()
+            .await
+    }
+
+    /// Transfer the token `token_id` from `sender` to `receiver_id`. If `sender` is not the
+    /// owner of the token, it needs to be approved with `approval_id`.
+    pub async fn nft_transfer(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        token_id: &str,
+        approval_id: Option<u64>,
+        memo: Option<&str>,
+    ) -> Result<ExecutionFinalResult> {
+        sender
+            .call(self.id(), "nft_transfer")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "token_id": token_id,
+                "approval_id": approval_id,
+                "memo": memo,
+            }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+    }
+
+    /// Transfer the token `token_id` from `sender` to the contract `receiver_id`, and call
+    /// its `nft_on_transfer` with `msg`. The transaction returns whether the token was kept
+    /// by the receiver.
+    pub async fn nft_transfer_call(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        token_id: &str,
+        approval_id: Option<u64>,
+        msg: &str,
+    ) -> Result<ExecutionFinalResult> {
+        sender
+            .call(self.id(), "nft_transfer_call")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "token_id": token_id,
+                "approval_id": approval_id,
+                "msg": msg,
+            }))
+            .deposit(ONE_YOCTO)
+            .gas(NFT_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// The token `token_id`, or `None` if it does not exist.
+    pub async fn nft_token(&self, token_id: &str) -> Result<Option<Token>> {
+        self.contract
+            .view("nft_token")
+            .args_json(json!({ "token_id": token_id }))
+            .await?
+            .json()
+    }
+
+    /// Metadata of the contract.
+    pub async fn nft_metadata(&self) -> Result<NftContractMetadata> {
+        self.contract.view("nft_metadata").await?.json()
+    }
+
+    /// Approve `account_id` to transfer the token `token_id` on behalf of its `owner`. If
+    /// `msg` is given, `nft_on_approve` is called on `account_id` with it, such as for
+    /// listing the token on a marketplace.
+    pub async fn nft_approve(
+        &self,
+        owner: &Account,
+        token_id: &str,
+        account_id: &AccountId,
+        msg: Option<&str>,
+    ) -> Result<ExecutionFinalResult> {
+        owner
+            .call(self.id(), "nft_approve")
+            .args_json(json!({
+                "token_id": token_id,
+                "account_id": account_id,
+                "msg": msg,
+            }))
+            .deposit(NFT_STORAGE_DEPOSIT)
+            .gas(NFT_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Revoke the approval of `account_id` to transfer the token `token_id`.
+    pub async fn nft_revoke(
+        &self,
+        owner: &Account,
+        token_id: &str,
+        account_id: &AccountId,
+    ) -> Result<ExecutionFinalResult> {
+        owner
+            .call(self.id(), "nft_revoke")
+            .args_json(json!({
+                "token_id": token_id,
+                "account_id": account_id,
+            }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+    }
+
+    /// Whether `approved_account_id` is approved to transfer the token `token_id`, and with
+    /// `approval_id` if given.
+    pub async fn nft_is_approved(
+        &self,
+        token_id: &str,
+        approved_account_id: &AccountId,
+        approval_id: Option<u64>,
+    ) -> Result<bool> {
+        self.contract
+            .view("nft_is_approved")
+            .args_json(json!({
+                "token_id": token_id,
+                "approved_account_id": approved_account_id,
+                "approval_id": approval_id,
+            }))
+            .await?
+            .json()
+    }
+
+    /// Total number of tokens in existence.
+    pub async fn nft_total_supply(&self) -> Result<u128> {
+        let supply: String = self.contract.view("nft_total_supply").await?.json()?;
+        parse_u128(&supply)
+    }
+
+    /// The tokens owned by `account_id`, paginated from the index `from_index` up to `limit`
+    /// tokens.
+    pub async fn nft_tokens_for_owner(
+        &self,
+        account_id: &AccountId,
+        from_index: Option<u128>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Token>> {
+        self.contract
+            .view("nft_tokens_for_owner")
+            .args_json(json!({
+                "account_id": account_id,
+                "from_index": from_index.map(|index| index.to_string()),
+                "limit": limit,
+            }))
+            .await?
+            .json()
+    }
+
+    /// Number of tokens owned by `account_id`.
+    pub async fn nft_supply_for_owner(&self, account_id: &AccountId) -> Result<u128> {
+        let supply: String = self
+            .contract
+            .view("nft_supply_for_owner")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json()?;
+        parse_u128(&supply)
+    }
+}
@@ -0,0 +1,83 @@
+//! BIP39 seed phrases, and SLIP-10 derivation of ED25519 keys from them as NEAR wallets do.
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+use crate::error::ErrorKind;
+use crate::result::Result;
+
+/// HD path NEAR wallets derive the key of an account from a seed phrase with, following the
+/// 397 coin type registered for NEAR in SLIP-44.
+pub const DEFAULT_HD_PATH: &str = "m/44'/397'/0'";
+
+/// Number of words of the seed phrases generated by NEAR wallets.
+const SEED_PHRASE_WORD_COUNT: usize = 12;
+
+/// Offset of hardened indices, the only ones SLIP-10 supports for ED25519.
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Generate a new english seed phrase from OS provided entropy.
+pub(crate) fn generate() -> String {
+    Mnemonic::generate(SEED_PHRASE_WORD_COUNT)
+        .expect("12 is a valid seed phrase word count")
+        .to_string()
+}
+
+/// Derive the ED25519 secret key seed at `hd_path` from an english `phrase`, with no
+/// passphrase.
+pub(crate) fn derive_ed25519(phrase: &str, hd_path: &str) -> Result<[u8; 32]> {
+    let mnemonic = Mnemonic::parse(phrase)
+        .map_err(|e| ErrorKind::DataConversion.full("invalid seed phrase", e))?;
+    let seed = mnemonic.to_seed("");
+
+    let (mut key, mut chain_code) = slip10_ed25519(b"ed25519 seed", &seed);
+    for index in parse_hd_path(hd_path)? {
+        let mut data = Vec::with_capacity(37);
+        data.push(0);
+        data.extend_from_slice(&key);
+        data.extend_from_slice(&index.to_be_bytes());
+        (key, chain_code) = slip10_ed25519(&chain_code, &data);
+    }
+
+    Ok(key)
+}
+
+/// Split `HMAC-SHA512(key, data)` into a key and a chain code.
+fn slip10_ed25519(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let output = mac.finalize().into_bytes();
+
+    let mut key = [0; 32];
+    let mut chain_code = [0; 32];
+    key.copy_from_slice(&output[..32]);
+    chain_code.copy_from_slice(&output[32..]);
+    (key, chain_code)
+}
+
+/// Parse an HD path such as `m/44'/397'/0'` into its hardened indices.
+fn parse_hd_path(hd_path: &str) -> Result<Vec<u32>> {
+    let invalid = |reason: &str| {
+        ErrorKind::DataConversion.message(format!("invalid HD path `{hd_path}`: {reason}"))
+    };
+
+    let mut segments = hd_path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid("expected it to start with `m`"));
+    }
+    segments
+        .map(|segment| {
+            let index = segment
+                .strip_suffix('\'')
+                .or_else(|| segment.strip_suffix('h'))
+                .ok_or_else(|| invalid("only hardened indices are supported for ED25519 keys"))?;
+            let index: u32 = index
+                .parse()
+                .ok()
+                .filter(|index| *index < HARDENED_OFFSET)
+                .ok_or_else(|| invalid("expected indices to be numbers below 2^31"))?;
+            Ok(index + HARDENED_OFFSET)
+        })
+        .collect()
+}
@@ -0,0 +1,194 @@
+//! Client for the [core staking pool] contract, bundled as `STAKING_POOL_WASM` and deployed
+//! with `Worker::deploy_staking_pool` with the `bundled-contracts` feature.
+//!
+//! Pairing the pool with [`Worker::fast_forward`] allows observing its semantics across
+//! epochs, such as unstaked balances only becoming withdrawable a few epochs later. Note
+//! that a pool only earns rewards once its stake makes it a validator.
+//!
+//! [core staking pool]: https://github.com/near/core-contracts/tree/master/staking-pool
+//! [`Worker::fast_forward`]: crate::Worker::fast_forward
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::result::{ExecutionFinalResult, Result};
+use crate::types::{Gas, NearToken, SecretKey};
+use crate::{Account, AccountId, Contract};
+
+/// The core staking pool contract, ported to near-sdk 5 from `examples/staking-pool-contract`,
+/// initialized with `new(owner_id, stake_public_key, reward_fee_fraction)`. Unstaked balances
+/// become withdrawable 4 epochs after unstaking.
+#[cfg(feature = "bundled-contracts")]
+pub const STAKING_POOL_WASM: &[u8] = include_bytes!("../../res/staking_pool.wasm");
+
+/// Gas attached to calls into the pool, which may restake or transfer with a callback.
+const STAKING_POOL_CALL_GAS: Gas = Gas::from_tgas(125);
+
+/// Fraction of the rewards the owner of a staking pool takes as a fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardFeeFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl RewardFeeFraction {
+    /// A fee of `numerator / denominator` of the rewards.
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+/// An account delegating to a staking pool, as returned by its `get_account` view.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingPoolAccount {
+    pub account_id: AccountId,
+    pub unstaked_balance: NearToken,
+    pub staked_balance: NearToken,
+    /// Whether the unstaked balance can be withdrawn, which only happens a few epochs after
+    /// unstaking.
+    pub can_withdraw: bool,
+}
+
+/// A staking pool contract, wrapping its [`Contract`] with typed calls to its methods. The
+/// gas required by each call is attached automatically.
+///
+/// Calls are signed by the given account and return their [`ExecutionFinalResult`], so that
+/// failures such as withdrawing too early can be asserted on. Views return their value.
+#[derive(Clone, Debug)]
+pub struct StakingPool {
+    contract: Contract,
+    stake_secret_key: Option<SecretKey>,
+}
+
+impl StakingPool {
+    /// Wrap a `contract` running the core staking pool.
+    pub fn new(contract: Contract) -> Self {
+        Self {
+            contract,
+            stake_secret_key: None,
+        }
+    }
+
+    /// Wrap a `contract` running the core staking pool, staking with `stake_secret_key`.
+    #[cfg(feature = "bundled-contracts")]
+    pub(crate) fn with_stake_secret_key(contract: Contract, stake_secret_key: SecretKey) -> Self {
+        Self {
+            contract,
+            stake_secret_key: Some(stake_secret_key),
+        }
+    }
+
+    /// Account ID of the staking pool.
+    pub fn id(&self) -> &AccountId {
+        self.contract.id()
+    }
+
+    /// The underlying [`Contract`], for calling methods not covered here.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Secret key of the key the pool stakes with, such as to run a validator node for it.
+    /// Only known for pools deployed with `Worker::deploy_staking_pool`.
+    pub fn stake_secret_key(&self) -> Option<&SecretKey> {
+        self.stake_secret_key.as_ref()
+    }
+
+    /// Deposit `amount` from `account` and stake all of its unstaked balance.
+    pub async fn deposit_and_stake(
+        &self,
+        account: &Account,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "deposit_and_stake")
+            .deposit(amount)
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Unstake `amount` of the staked balance of `account`. It becomes withdrawable a few
+    /// epochs later.
+    pub async fn unstake(
+        &self,
+        account: &Account,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "unstake")
+            .args_json(json!({ "amount": amount }))
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Unstake the entire staked balance of `account`.
+    pub async fn unstake_all(&self, account: &Account) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "unstake_all")
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Withdraw `amount` of the unstaked balance of `account` back to it.
+    pub async fn withdraw(
+        &self,
+        account: &Account,
+        amount: NearToken,
+    ) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "withdraw")
+            .args_json(json!({ "amount": amount }))
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Withdraw the entire unstaked balance of `account` back to it.
+    pub async fn withdraw_all(&self, account: &Account) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "withdraw_all")
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Distribute the rewards earned since the last epoch the pool was pinged in. This is
+    /// done by every other call as well.
+    pub async fn ping(&self, account: &Account) -> Result<ExecutionFinalResult> {
+        account
+            .call(self.id(), "ping")
+            .gas(STAKING_POOL_CALL_GAS)
+            .transact()
+            .await
+    }
+
+    /// Balances of `account_id` in the pool.
+    pub async fn get_account(&self, account_id: &AccountId) -> Result<StakingPoolAccount> {
+        self.contract
+            .view("get_account")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json()
+    }
+
+    /// Total balance staked by the pool, including the rewards not yet distributed.
+    pub async fn get_total_staked_balance(&self) -> Result<NearToken> {
+        self.contract.view("get_total_staked_balance").await?.json()
+    }
+
+    /// Fraction of the rewards taken by the owner of the pool.
+    pub async fn get_reward_fee_fraction(&self) -> Result<RewardFeeFraction> {
+        self.contract.view("get_reward_fee_fraction").await?.json()
+    }
+
+    /// Owner of the pool.
+    pub async fn get_owner_id(&self) -> Result<AccountId> {
+        self.contract.view("get_owner_id").await?.json()
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use near_account_id::AccountId;
+use near_primitives::views::StateChangeValueView;
+
+use crate::CryptoHash;
+use crate::types::{AccessKey, AccountDetails, BlockHeight, PublicKeyHandle};
+
+/// All the [`StateChange`]s that happened within a single block. Usually given from
+/// querying [`Worker::state_changes`] or streaming [`Worker::state_changes_range`].
+///
+/// [`Worker::state_changes`]: crate::Worker::state_changes
+/// [`Worker::state_changes_range`]: crate::Worker::state_changes_range
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StateChanges {
+    /// Hash of the block the changes happened in.
+    pub block_hash: CryptoHash,
+    /// Height of the block the changes happened in.
+    pub block_height: BlockHeight,
+    /// The changes made to the state, grouped by kind: account changes come first, followed
+    /// by access key, contract code and finally contract storage changes. Only within a kind
+    /// are the changes in the order they were applied. Note that the same piece of state can
+    /// be changed multiple times within a block, such as by different receipts, in which case
+    /// each of the changes will be present.
+    pub changes: Vec<StateChange>,
+}
+
+impl StateChanges {
+    /// Iterate over the changes made to the state of `account_id`.
+    pub fn for_account<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> impl Iterator<Item = &'a StateChange> + 'a {
+        self.changes
+            .iter()
+            .filter(move |change| change.account_id() == account_id)
+    }
+
+    /// Iterate over the contract storage keys that were either updated or deleted.
+    pub fn data_keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.changes.iter().filter_map(|change| match change {
+            StateChange::DataUpdate { key, .. } | StateChange::DataDeletion { key, .. } => {
+                Some(key.as_slice())
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns true if no changes were made to the state.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// The difference in the storage of a contract between two blocks. Usually given from
+/// calling into [`Contract::state_diff`].
+///
+/// [`Contract::state_diff`]: crate::Contract::state_diff
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StateDiff {
+    /// Keys that did not exist at the starting block, along with their new values.
+    pub added: HashMap<Vec<u8>, Vec<u8>>,
+    /// Keys that no longer exist at the ending block, along with their old values.
+    pub removed: HashMap<Vec<u8>, Vec<u8>>,
+    /// Keys that exist at both blocks, but whose values differ.
+    pub modified: HashMap<Vec<u8>, ValueChange>,
+}
+
+impl StateDiff {
+    /// Returns true if the storage is the same at both blocks.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Iterate over all the keys that were either added, removed or modified.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.added
+            .keys()
+            .chain(self.removed.keys())
+            .chain(self.modified.keys())
+            .map(Vec::as_slice)
+    }
+}
+
+/// The old and new value of a storage key modified within a [`StateDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueChange {
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// A single change made to the state of an account within a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StateChange {
+    /// The account was created or its details, such as its balance, were updated.
+    AccountUpdate {
+        account_id: AccountId,
+        account: AccountDetails,
+    },
+    /// The account was deleted.
+    AccountDeletion { account_id: AccountId },
+    /// An access key was added to the account, or an existing one was updated such as
+    /// by having its nonce incremented. The key is given as stored on chain, which for
+    /// ML-DSA-65 keys is only the hash of the public key.
+    AccessKeyUpdate {
+        account_id: AccountId,
+        public_key_handle: PublicKeyHandle,
+        access_key: AccessKey,
+    },
+    /// An access key was removed from the account.
+    AccessKeyDeletion {
+        account_id: AccountId,
+        public_key_handle: PublicKeyHandle,
+    },
+    /// A contract storage key was written to.
+    DataUpdate {
+        account_id: AccountId,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    /// A contract storage key was removed.
+    DataDeletion { account_id: AccountId, key: Vec<u8> },
+    /// A contract was deployed to the account.
+    ContractCodeUpdate {
+        account_id: AccountId,
+        code: Vec<u8>,
+    },
+    /// The contract deployed to the account was removed.
+    ContractCodeDeletion { account_id: AccountId },
+}
+
+impl StateChange {
+    /// The account whose state was changed.
+    pub fn account_id(&self) -> &AccountId {
+        match self {
+            Self::AccountUpdate { account_id, .. }
+            | Self::AccountDeletion { account_id }
+            | Self::AccessKeyUpdate { account_id, .. }
+            | Self::AccessKeyDeletion { account_id, .. }
+            | Self::DataUpdate { account_id, .. }
+            | Self::DataDeletion { account_id, .. }
+            | Self::ContractCodeUpdate { account_id, .. }
+            | Self::ContractCodeDeletion { account_id } => account_id,
+        }
+    }
+
+    /// Convert from the view returned by the RPC. Changes that are not represented by
+    /// [`StateChange`], such as gas key nonce updates, are converted into `None`.
+    pub(crate) fn from_view(view: StateChangeValueView) -> Option<Self> {
+        let change = match view {
+            StateChangeValueView::AccountUpdate {
+                account_id,
+                account,
+            } => Self::AccountUpdate {
+                account_id,
+                account: account.into(),
+            },
+            StateChangeValueView::AccountDeletion { account_id } => {
+                Self::AccountDeletion { account_id }
+            }
+            StateChangeValueView::AccessKeyUpdate {
+                account_id,
+                public_key,
+                access_key,
+            } => Self::AccessKeyUpdate {
+                account_id,
+                public_key_handle: PublicKeyHandle(public_key),
+                access_key: access_key.into(),
+            },
+            StateChangeValueView::AccessKeyDeletion {
+                account_id,
+                public_key,
+            } => Self::AccessKeyDeletion {
+                account_id,
+                public_key_handle: PublicKeyHandle(public_key),
+            },
+            StateChangeValueView::DataUpdate {
+                account_id,
+                key,
+                value,
+            } => Self::DataUpdate {
+                account_id,
+                key: key.into(),
+                value: value.into(),
+            },
+            StateChangeValueView::DataDeletion { account_id, key } => Self::DataDeletion {
+                account_id,
+                key: key.into(),
+            },
+            StateChangeValueView::ContractCodeUpdate { account_id, code } => {
+                Self::ContractCodeUpdate { account_id, code }
+            }
+            StateChangeValueView::ContractCodeDeletion { account_id } => {
+                Self::ContractCodeDeletion { account_id }
+            }
+            StateChangeValueView::GasKeyNonceUpdate { .. } => return None,
+        };
+
+        Some(change)
+    }
+}
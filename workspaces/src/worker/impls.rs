@@ -1,19 +1,38 @@
+use std::ops::Range;
+use std::path::Path;
+
 use near_primitives::views::StatusResponse;
 
 use crate::network::{Info, RootAccountSubaccountCreator, Sandbox, Testnet};
 use crate::network::{NetworkClient, NetworkInfo};
 use crate::operations::{CallTransaction, Function};
-use crate::result::{ExecutionFinalResult, Result};
+use crate::result::{Execution, ExecutionFinalResult, Result};
 use crate::rpc::client::Client;
-use crate::rpc::patch::{ImportContractTransaction, PatchTransaction};
+use crate::rpc::patch::{
+    ImportContractTransaction, ImportContractsTransaction, PatchManyTransaction, PatchTransaction,
+};
 use crate::rpc::query::{
-    GasPrice, Query, QueryChunk, ViewAccessKey, ViewAccessKeyList, ViewAccount, ViewBlock,
-    ViewCode, ViewFunction, ViewState,
+    GasPrice, Query, QueryChunk, QueryStateChanges, ViewAccessKey, ViewAccessKeyList, ViewAccount,
+    ViewBlock, ViewCode, ViewFunction, ViewState,
+};
+use crate::rpc::records;
+use crate::rpc::stream::{
+    AccountActivityStream, BlockRangeStream, BlockStream, StateChangesStream, StatePageStream,
+};
+use crate::testing::ReplayTransactions;
+use crate::types::{
+    AccessKey, AccountId, BlockHeight, InMemorySigner, NearToken, PublicKey, SecretKey,
 };
-use crate::types::{AccountId, InMemorySigner, NearToken, PublicKey};
 use crate::worker::Worker;
 use crate::{Account, Network};
 
+#[cfg(feature = "bundled-contracts")]
+use crate::{
+    Contract,
+    error::ErrorKind,
+    types::{ft::FungibleToken, linkdrop::Linkdrop, multisig::MultisigFactory},
+};
+
 #[cfg(feature = "experimental")]
 use {
     near_chain_configs::{GenesisConfig, ProtocolConfigView},
@@ -32,6 +51,9 @@ use {
     },
 };
 
+/// Balance transferred to the implicit accounts created on sandbox.
+const IMPLICIT_ACCOUNT_DEPOSIT: NearToken = NearToken::from_near(100);
+
 impl<T: ?Sized> Clone for Worker<T> {
     fn clone(&self) -> Self {
         Self {
@@ -118,6 +140,20 @@ where
         QueryChunk::new(self.client())
     }
 
+    /// View the typed [`StateChange`]s that happened within a block once awaited. Supply
+    /// additional parameters such as [`block_height`] or [`block_hash`] to get the changes
+    /// of a specific block, otherwise the latest block is used. Changes can be narrowed down
+    /// to specific accounts with [`account`], or to contract storage keys with [`key_prefix`].
+    ///
+    /// [`StateChange`]: crate::types::StateChange
+    /// [`block_height`]: QueryStateChanges::block_height
+    /// [`block_hash`]: QueryStateChanges::block_hash
+    /// [`account`]: QueryStateChanges::account
+    /// [`key_prefix`]: QueryStateChanges::key_prefix
+    pub fn state_changes(&self) -> QueryStateChanges<'_> {
+        QueryStateChanges::new(self.client())
+    }
+
     /// Views the [`AccessKey`] of the account specified by [`AccountId`] associated with
     /// the [`PublicKey`]
     ///
@@ -280,6 +316,52 @@ where
             function,
         )
     }
+
+    /// Subscribe to new blocks produced by the network. Returns a [`BlockStream`] which
+    /// yields every new block as the chain advances, and allows us to specify further
+    /// details like the [`finality`] of blocks or the [`poll_interval`] to check for them.
+    ///
+    /// [`finality`]: BlockStream::finality
+    /// [`poll_interval`]: BlockStream::poll_interval
+    pub fn blocks(&self) -> BlockStream {
+        BlockStream::new(self.clone().coerce())
+    }
+
+    /// Stream all the blocks within the given `range` of block heights. Returns a
+    /// [`BlockRangeStream`] which yields the blocks in order of height, while fetching
+    /// up to a [`concurrency`] amount of them from the network at the same time.
+    ///
+    /// [`concurrency`]: BlockRangeStream::concurrency
+    pub fn blocks_range(&self, range: Range<BlockHeight>) -> BlockRangeStream {
+        BlockRangeStream::new(self.clone().coerce(), range)
+    }
+
+    /// View the state of a contract a page of key value pairs at a time. Returns a
+    /// [`StatePageStream`] which is able to view states larger than what [`view_state`]
+    /// can view in a single request, such as the states of large mainnet contracts.
+    ///
+    /// [`view_state`]: Worker::view_state
+    pub fn view_state_pages(&self, contract_id: &AccountId) -> StatePageStream {
+        StatePageStream::new(self.clone().coerce(), contract_id.clone())
+    }
+
+    /// Stream the typed [`StateChanges`] of every block within the given `range` of block
+    /// heights. Returns a [`StateChangesStream`] which yields the changes in order of height,
+    /// and can be narrowed down with filters like [`account`] or [`key_prefix`].
+    ///
+    /// [`StateChanges`]: crate::types::StateChanges
+    /// [`account`]: StateChangesStream::account
+    /// [`key_prefix`]: StateChangesStream::key_prefix
+    pub fn state_changes_range(&self, range: Range<BlockHeight>) -> StateChangesStream {
+        StateChangesStream::new(self.clone().coerce(), range)
+    }
+
+    /// Watch the activity of an account as new blocks are produced. Returns an
+    /// [`AccountActivityStream`] which yields every receipt executed on the account
+    /// specified by [`AccountId`], along with the outcome of its execution.
+    pub fn watch_account(&self, account_id: &AccountId) -> AccountActivityStream {
+        AccountActivityStream::new(self.clone().coerce(), account_id.clone())
+    }
 }
 
 impl Worker<Testnet> {
@@ -307,6 +389,112 @@ impl Worker<Sandbox> {
         Ok(Account::new(signer, self.clone().coerce()))
     }
 
+    /// The wrapped NEAR token deployed to `wrap.near` when the sandbox was built with
+    /// `with_standard_contracts()`. Its contract is signed by `wrap.near` itself.
+    ///
+    /// Fails if the sandbox was built without the standard contracts.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn wrap_near(&self) -> Result<FungibleToken> {
+        let signer = self.workspace.wrap_near_signer()?;
+        Ok(FungibleToken::new(Contract::new(
+            signer,
+            self.clone().coerce(),
+        )))
+    }
+
+    /// The linkdrop contract on the `near` account when the sandbox was built with
+    /// `with_standard_contracts()`. Its contract is signed by `near` itself.
+    ///
+    /// Fails if the sandbox was built without the standard contracts.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn linkdrop(&self) -> Result<Linkdrop> {
+        let signer = self.workspace.near_signer()?;
+        Ok(Linkdrop::new(Contract::new(signer, self.clone().coerce())))
+    }
+
+    /// The multisig factory on the `multisig.near` account when the sandbox was built with
+    /// `with_standard_contracts()`. Its contract is signed by `multisig.near` itself.
+    ///
+    /// Fails if the sandbox was built without the standard contracts.
+    #[cfg(feature = "bundled-contracts")]
+    pub fn multisig_factory(&self) -> Result<MultisigFactory> {
+        let signer = self.workspace.multisig_factory_signer()?;
+        Ok(MultisigFactory::new(Contract::new(
+            signer,
+            self.clone().coerce(),
+        )))
+    }
+
+    /// Create the `.near` account `id` the way wallets do on mainnet: by calling
+    /// `create_account` on the linkdrop contract of `near`, funded with `amount` by the root
+    /// account. This requires the sandbox to be built with `with_standard_contracts()`.
+    ///
+    /// Fails if the account could not be created, such as when it already exists, in which
+    /// case the linkdrop contract refunds the deposit.
+    #[cfg(feature = "bundled-contracts")]
+    pub async fn create_near_account(
+        &self,
+        id: AccountId,
+        sk: SecretKey,
+        amount: NearToken,
+    ) -> Result<Execution<Account>> {
+        let details = self
+            .linkdrop()?
+            .create_account(&self.root_account()?, &id, &sk.public_key(), amount)
+            .await?;
+        if !details.clone().json::<bool>()? {
+            return Err(ErrorKind::Execution.message(format!(
+                "the linkdrop contract could not create the account {id}"
+            )));
+        }
+
+        Ok(Execution {
+            result: Account::from_secret_key(id, sk, self),
+            details,
+        })
+    }
+
+    /// Create the implicit account of `sk`, an ED25519 key, by transferring NEAR to its
+    /// [`PublicKey::implicit_account_id`] from the root account. As on mainnet, the transfer
+    /// creates the account with the public key of `sk` as its full access key.
+    pub async fn create_implicit_account(&self, sk: SecretKey) -> Result<Execution<Account>> {
+        let id = sk.public_key().implicit_account_id()?;
+        self.fund_implicit_account(id, sk).await
+    }
+
+    /// Create the ETH-implicit account of `sk`, a SECP256K1 key, by transferring NEAR to its
+    /// [`PublicKey::eth_implicit_account_id`] from the root account. As on mainnet, the
+    /// transfer creates the account with the wallet contract, which executes Ethereum
+    /// transactions relayed to its `rlp_execute` method.
+    ///
+    /// Such accounts have no access key, so the sandbox is also patched with the public key of
+    /// `sk` as a full access key, allowing the returned account to sign NEAR transactions.
+    pub async fn create_eth_implicit_account(&self, sk: SecretKey) -> Result<Execution<Account>> {
+        let id = sk.public_key().eth_implicit_account_id()?;
+        let execution = self.fund_implicit_account(id.clone(), sk.clone()).await?;
+        self.patch(&id)
+            .access_key(sk.public_key(), AccessKey::full_access())
+            .transact()
+            .await?;
+        Ok(execution)
+    }
+
+    async fn fund_implicit_account(
+        &self,
+        id: AccountId,
+        sk: SecretKey,
+    ) -> Result<Execution<Account>> {
+        let details = self
+            .root_account()?
+            .transfer_near(&id, IMPLICIT_ACCOUNT_DEPOSIT)
+            .await?;
+        details.clone().into_result()?;
+        Ok(Execution {
+            result: Account::from_secret_key(id, sk, self),
+            details,
+        })
+    }
+
     /// Import a contract from the given network, and return us a [`ImportContractTransaction`]
     /// which allows to specify further details, such as being able to import contract data and
     /// how far back in time we wanna grab the contract.
@@ -318,6 +506,39 @@ impl Worker<Sandbox> {
         ImportContractTransaction::new(id, worker.clone().coerce(), self.clone())
     }
 
+    /// Import a set of contracts from the given network along with everything they depend
+    /// on, and return us a [`ImportContractsTransaction`] which allows to specify further
+    /// details, such as additional dependencies or discovering them from contract state.
+    pub fn import_contracts(
+        &self,
+        worker: &Worker<impl Network + 'static>,
+        roots: impl IntoIterator<Item = AccountId>,
+    ) -> ImportContractsTransaction {
+        ImportContractsTransaction::new(
+            roots.into_iter().collect(),
+            worker.clone().coerce(),
+            self.clone(),
+        )
+    }
+
+    /// Replay the historical function calls made into `contract_id` on the given network from
+    /// the transactions included within `range` of block heights, and return us a
+    /// [`ReplayTransactions`] which allows to specify a candidate wasm to replay them against.
+    /// This allows shadow testing an upgrade of a contract against real traffic.
+    pub fn replay_transactions(
+        &self,
+        worker: &Worker<impl Network + 'static>,
+        contract_id: &AccountId,
+        range: Range<BlockHeight>,
+    ) -> ReplayTransactions {
+        ReplayTransactions::new(
+            worker.clone().coerce(),
+            self.clone(),
+            contract_id.clone(),
+            range,
+        )
+    }
+
     /// Start patching the state of the account specified by the [`AccountId`]. This will create
     /// a [`PatchTransaction`] that will allow us to patch access keys, code, and contract state.
     /// This is similar to functions like [`Account::batch`] where we can perform multiple actions
@@ -326,6 +547,13 @@ impl Worker<Sandbox> {
         PatchTransaction::new(self, account_id.clone())
     }
 
+    /// Start patching the state of many accounts at once. This will create a
+    /// [`PatchManyTransaction`] that accumulates the patches of every account, and submits
+    /// them together in a single request.
+    pub fn patch_many(&self) -> PatchManyTransaction {
+        PatchManyTransaction::new(self)
+    }
+
     /// Patch state into the sandbox network, given a prefix key and value. This will allow us
     /// to set contract state that we have acquired in some manner, where we are able to test
     /// random cases that are hard to come up naturally as state evolves.
@@ -338,6 +566,28 @@ impl Worker<Sandbox> {
         self.workspace.patch_state(contract_id, key, value).await
     }
 
+    /// Dump the accounts, access keys, code and contract data of the given accounts into a
+    /// JSON file at `path`, as a list of `StateRecord`s in the same format as genesis records.
+    /// All accounts are dumped at the latest block. The file can be loaded back into this or
+    /// another sandbox with [`Worker::load_records`].
+    pub async fn dump_accounts<'a>(
+        &self,
+        account_ids: impl IntoIterator<Item = &'a AccountId>,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let records =
+            records::dump_records(self, account_ids.into_iter().cloned().collect()).await?;
+        records::write_records(path.as_ref(), &records).await
+    }
+
+    /// Load a JSON file of `StateRecord`s, such as one written by [`Worker::dump_accounts`],
+    /// and patch them into sandbox in order. Records are patched in batches, so large contract
+    /// states do not exceed the limits of a single patch request.
+    pub async fn load_records(&self, path: impl AsRef<Path>) -> Result<()> {
+        let records = records::read_records(path.as_ref()).await?;
+        records::load_records(self, records).await
+    }
+
     /// Fast forward to a point in the future. The delta block height is supplied to tell the
     /// network to advanced a certain amount of blocks. This comes with the advantage only having
     /// to wait a fraction of the time it takes to produce the same number of blocks.
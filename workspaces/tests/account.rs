@@ -1,6 +1,7 @@
 #![recursion_limit = "256"]
 use near_token::NearToken;
-use near_workspaces::types::{KeyType, SecretKey};
+use near_workspaces::Account;
+use near_workspaces::types::{AccessKey, DEFAULT_HD_PATH, KeyType, SecretKey, StateChange};
 use serde_json::{Map, Value};
 use test_log::test;
 
@@ -143,3 +144,152 @@ async fn test_delete_account() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_implicit_accounts() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+
+    let sk = SecretKey::from_random(KeyType::ED25519);
+    let implicit = worker
+        .create_implicit_account(sk.clone())
+        .await?
+        .into_result()?;
+    assert_eq!(implicit.id().len(), 64);
+    assert_eq!(implicit.id(), &sk.public_key().implicit_account_id()?);
+    assert!(sk.public_key().eth_implicit_account_id().is_err());
+
+    let eth_sk = SecretKey::from_random(KeyType::SECP256K1);
+    let eth_implicit = worker
+        .create_eth_implicit_account(eth_sk.clone())
+        .await?
+        .into_result()?;
+    assert!(eth_implicit.id().as_str().starts_with("0x"));
+    assert_eq!(eth_implicit.id().len(), 42);
+    assert!(eth_sk.public_key().implicit_account_id().is_err());
+
+    // Both accounts sign with their own keys.
+    eth_implicit
+        .transfer_near(implicit.id(), NearToken::from_near(1))
+        .await?
+        .into_result()?;
+    implicit
+        .transfer_near(eth_implicit.id(), NearToken::from_near(2))
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_account_from_seed_phrase() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (phrase, sk) = SecretKey::generate_seed_phrase();
+    let (id, _) = worker.generate_dev_account_credentials();
+    worker.create_tla(id.clone(), sk).await?.into_result()?;
+
+    let account = Account::from_seed_phrase(id, &phrase, DEFAULT_HD_PATH, &worker)?;
+    account
+        .transfer_near(worker.root_account()?.id(), NearToken::from_near(1))
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_mldsa_key_rotation() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let mut account = worker.dev_create_account().await?;
+    let old_pk = account.secret_key().public_key();
+
+    // Rotate the full access key of the account to a post-quantum one.
+    let pq_sk = SecretKey::from_random(KeyType::MLDSA65);
+    let outcome = account
+        .batch(account.id())
+        .add_key(pq_sk.public_key(), AccessKey::full_access())
+        .transact()
+        .await?
+        .into_result()?;
+
+    // The added key shows up in the state changes by its handle.
+    let changes = worker
+        .state_changes()
+        .block_hash(outcome.receipt_outcomes()[0].block_hash)
+        .account(account.id())
+        .await?;
+    assert!(changes.changes.iter().any(|change| matches!(
+        change,
+        StateChange::AccessKeyUpdate { public_key_handle, .. }
+            if public_key_handle.matches(&pq_sk.public_key())
+    )));
+    account.set_secret_key(pq_sk.clone());
+    account
+        .batch(account.id())
+        .delete_key(old_pk)
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Only the hash of the key is stored, so it is listed by its handle.
+    let keys = account.view_access_keys().await?;
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].public_key.is_none());
+    assert!(keys[0].public_key_handle.matches(&pq_sk.public_key()));
+
+    // Transactions are signed with the ML-DSA-65 key.
+    account
+        .transfer_near(worker.root_account()?.id(), NearToken::from_near(1))
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_encrypted_credentials() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let account = worker.dev_create_account().await?;
+    let savedir = tempfile::tempdir()?;
+
+    account
+        .store_credentials_encrypted(savedir.path(), "correct horse")
+        .await?;
+    let path = savedir
+        .path()
+        .join(format!("{}.encrypted.json", account.id()));
+    let contents = fs::read_to_string(&path)?;
+    assert!(!contents.contains(&account.secret_key().expose_secret()));
+
+    let loaded = Account::from_encrypted_file(&path, "correct horse", &worker)?;
+    assert_eq!(loaded.id(), account.id());
+    assert_eq!(loaded.secret_key(), account.secret_key());
+    assert!(Account::from_encrypted_file(&path, "battery staple", &worker).is_err());
+
+    // Secret keys are redacted from debug output.
+    assert!(!format!("{:?}", account.secret_key()).contains(&account.secret_key().expose_secret()));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_encrypted_credentials_cost_limit() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let account = worker.dev_create_account().await?;
+    let savedir = tempfile::tempdir()?;
+
+    account
+        .store_credentials_encrypted(savedir.path(), "correct horse")
+        .await?;
+    let path = savedir
+        .path()
+        .join(format!("{}.encrypted.json", account.id()));
+
+    // Key derivation parameters above those the file was written with are rejected up front.
+    let mut credentials: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    credentials["m_cost"] = Value::from(4 * 1024 * 1024);
+    fs::write(&path, credentials.to_string())?;
+    let err = Account::from_encrypted_file(&path, "correct horse", &worker).unwrap_err();
+    assert!(err.to_string().contains("exceed the maximum"));
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use futures::StreamExt;
+use serde_json::json;
+use test_log::test;
+
+use near_workspaces::types::Finality;
+
+#[test(tokio::test)]
+async fn test_blocks_stream() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+
+    let blocks = worker
+        .blocks()
+        .finality(Finality::Optimistic)
+        .take(3)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(blocks.len(), 3);
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[1].header().prev_hash(), pair[0].hash());
+        assert!(pair[1].height() > pair[0].height());
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_blocks_stream_across_fast_forward() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+
+    let mut blocks = worker.blocks();
+    let first = blocks.next().await.unwrap()?;
+
+    worker.fast_forward(100).await?;
+
+    // Heights skipped by fast forwarding do not stop the stream from yielding the next block.
+    let second = blocks.next().await.unwrap()?;
+    assert_eq!(second.header().prev_hash(), first.hash());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_blocks_range() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+
+    // Wait for enough blocks to be produced, so the range below exists on chain.
+    worker.blocks().take(2).collect::<Vec<_>>().await;
+    let latest = worker.view_block().await?.height();
+    let start = latest.saturating_sub(5);
+
+    let blocks = worker
+        .blocks_range(start..latest)
+        .concurrency(2)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert!(!blocks.is_empty());
+    for pair in blocks.windows(2) {
+        assert!(pair[1].height() > pair[0].height());
+    }
+    assert!(blocks.iter().all(|b| (start..latest).contains(&b.height())));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_watch_account() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker
+        .dev_deploy(include_bytes!("../../examples/res/status_message.wasm"))
+        .await?;
+
+    let mut activity = worker.watch_account(contract.id());
+    // Start watching in the background, so the watcher is polling before the transaction is sent.
+    let watcher = tokio::spawn(async move {
+        while let Some(activity) = activity.next().await {
+            let activity = activity?;
+            if activity.calls("set_status") {
+                return anyhow::Ok(activity);
+            }
+        }
+        anyhow::bail!("account activity stream ended unexpectedly")
+    });
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    let outcome = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let activity = tokio::time::timeout(std::time::Duration::from_secs(60), watcher).await???;
+    assert_eq!(&activity.predecessor_id, contract.id());
+    assert_eq!(&activity.receiver_id, contract.id());
+    assert_eq!(
+        activity.transaction_hash,
+        outcome.outcome().transaction_hash
+    );
+    assert!(activity.is_success());
+
+    let call = &activity.function_calls[0];
+    assert_eq!(call.method_name, "set_status");
+    assert_eq!(
+        call.args_json::<serde_json::Value>()?,
+        json!({ "message": "hello" })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "unstable")]
+use test_log::test;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[test(tokio::test)]
+async fn test_cache_queries_at_pinned_block() -> anyhow::Result<()> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let contract = sandbox
+        .dev_deploy(&std::fs::read(STATUS_MSG_WASM_FILEPATH)?)
+        .await?;
+    let height = sandbox.view_block().await?.height();
+
+    let cache_dir = tempfile::tempdir()?;
+    let worker = near_workspaces::custom(&sandbox.rpc_addr())
+        .cache_dir(cache_dir.path())
+        .await?;
+
+    let contract_id = contract.id().clone();
+    let code = worker.view_code(&contract_id).block_height(height).await?;
+    let account = worker
+        .view_account(&contract_id)
+        .block_height(height)
+        .await?;
+
+    // Queries not pinned to a block are never cached.
+    worker.view_code(&contract_id).await?;
+
+    let network_dir = std::fs::read_dir(cache_dir.path())?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("cache directory is empty"))??;
+    let entries = std::fs::read_dir(network_dir.path().join(contract_id.as_str()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(entries.len(), 2);
+
+    // Cached queries are still served once the network is gone, which requires opting into
+    // working offline to build the network.
+    let rpc_addr = sandbox.rpc_addr();
+    drop(contract);
+    drop(sandbox);
+    assert!(
+        near_workspaces::custom(&rpc_addr)
+            .cache_dir(cache_dir.path())
+            .await
+            .is_err()
+    );
+    let worker = near_workspaces::custom(&rpc_addr)
+        .cache_dir(cache_dir.path())
+        .offline()
+        .await?;
+    let cached = worker.view_code(&contract_id).block_height(height).await?;
+    assert_eq!(cached, code);
+    let cached = worker
+        .view_account(&contract_id)
+        .block_height(height)
+        .await?;
+    assert_eq!(cached, account);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_cache_unsupported_for_sandbox() -> anyhow::Result<()> {
+    let cache_dir = tempfile::tempdir()?;
+    let result = near_workspaces::sandbox().cache_dir(cache_dir.path()).await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_offline_requires_cache_dir() -> anyhow::Result<()> {
+    let result = near_workspaces::custom("http://localhost:3030")
+        .offline()
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
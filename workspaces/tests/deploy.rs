@@ -5,7 +5,7 @@ use test_log::test;
 use near_workspaces::network::ValidatorKey;
 use near_workspaces::{DevNetwork, Worker};
 
-const NFT_WASM_FILEPATH: &str = "../examples/res/non_fungible_token.wasm";
+const NFT_WASM_FILEPATH: &str = "./res/non_fungible_token.wasm";
 const EXPECTED_NFT_METADATA: &str = r#"{
   "spec": "nft-1.0.0",
   "name": "Example NEAR non-fungible token",
@@ -0,0 +1,55 @@
+use serde_json::json;
+use test_log::test;
+
+use near_workspaces::operations::Function;
+use near_workspaces::testing::{DiffTest, DivergenceKind};
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+const ADDER_WASM_FILEPATH: &str = "../examples/res/adder.wasm";
+
+#[test(tokio::test)]
+async fn test_diff_identical() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let wasm = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+    let caller = worker.dev_create_account().await?;
+
+    let report = DiffTest::new(&worker, &wasm, &wasm)
+        .caller(&caller)
+        .call(Function::new("set_status").args_json(json!({ "message": "hello" })))
+        .view(Function::new("get_status").args_json(json!({ "account_id": caller.id() })))
+        .gas_tolerance(5)
+        .run()
+        .await?;
+    assert!(report.is_identical(), "{report:?}");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_diff_divergent() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let status_msg = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+    let adder = std::fs::read(ADDER_WASM_FILEPATH)?;
+
+    let report = DiffTest::new(&worker, &status_msg, &adder)
+        .call(Function::new("set_status").args_json(json!({ "message": "hello" })))
+        .run()
+        .await?;
+    assert!(!report.is_identical());
+    assert!(report.divergences.iter().all(|d| d.step == 0));
+    assert!(report.divergences.iter().any(|d| matches!(
+        &d.kind,
+        DivergenceKind::Failure {
+            a: None,
+            b: Some(_)
+        }
+    )));
+    assert!(
+        report
+            .divergences
+            .iter()
+            .any(|d| matches!(&d.kind, DivergenceKind::State(diff) if !diff.removed.is_empty()))
+    );
+
+    Ok(())
+}
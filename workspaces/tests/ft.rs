@@ -0,0 +1,52 @@
+#![cfg(feature = "bundled-contracts")]
+use near_workspaces::types::ft::FungibleTokenMetadata;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_fungible_token() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let metadata = FungibleTokenMetadata::new("Example Token", "EXT", 24);
+    let ft = worker.deploy_ft(1_000_000, metadata.clone()).await?;
+    let owner = ft.contract().as_account();
+    let alice = worker.dev_create_account().await?;
+
+    assert_eq!(ft.ft_metadata().await?, metadata);
+    assert_eq!(ft.ft_total_supply().await?, 1_000_000);
+    assert_eq!(ft.ft_balance_of(owner.id()).await?, 1_000_000);
+
+    // Transfers to unregistered accounts fail.
+    let result = ft.ft_transfer(owner, alice.id(), 100, None).await?;
+    assert!(result.is_failure());
+    assert!(ft.storage_balance_of(alice.id()).await?.is_none());
+
+    ft.storage_deposit(owner, alice.id()).await?.into_result()?;
+    let bounds = ft.storage_balance_bounds().await?;
+    let balance = ft.storage_balance_of(alice.id()).await?.unwrap();
+    assert_eq!(balance.total, bounds.min);
+
+    ft.ft_transfer(owner, alice.id(), 100, Some("hello"))
+        .await?
+        .into_result()?;
+    assert_eq!(ft.ft_balance_of(alice.id()).await?, 100);
+    assert_eq!(ft.ft_balance_of(owner.id()).await?, 999_900);
+
+    // Transferring to an account without `ft_on_transfer` refunds the tokens.
+    let receiver = worker.deploy_mock().await?;
+    ft.storage_deposit(owner, receiver.id())
+        .await?
+        .into_result()?;
+    let used: String = ft
+        .ft_transfer_call(&alice, receiver.id(), 50, "")
+        .await?
+        .json()?;
+    assert_eq!(used, "0");
+    assert_eq!(ft.ft_balance_of(alice.id()).await?, 100);
+
+    // Unregistering requires giving up the remaining tokens.
+    assert!(ft.storage_unregister(&alice, false).await?.is_failure());
+    ft.storage_unregister(&alice, true).await?.into_result()?;
+    assert!(ft.storage_balance_of(alice.id()).await?.is_none());
+    assert_eq!(ft.ft_total_supply().await?, 999_900);
+
+    Ok(())
+}
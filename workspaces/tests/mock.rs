@@ -0,0 +1,114 @@
+#![cfg(feature = "bundled-contracts")]
+use near_workspaces::operations::Function;
+use near_workspaces::testing::MockContract;
+use near_workspaces::types::NearToken;
+use serde_json::json;
+use test_log::test;
+use wasmparser::{Parser, Payload, Validator};
+
+/// Names of the functions exported by `wasm`.
+fn exports(wasm: &[u8]) -> anyhow::Result<Vec<String>> {
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::ExportSection(reader) = payload? {
+            for export in reader {
+                exports.push(export?.name.to_string());
+            }
+        }
+    }
+    Ok(exports)
+}
+
+#[test]
+fn test_mock_wasm() -> anyhow::Result<()> {
+    let empty = MockContract::wasm(&[])?;
+    Validator::new().validate_all(&empty)?;
+    assert_eq!(exports(&empty)?, ["memory", "mock_configure"]);
+
+    let wasm = MockContract::wasm(&["ft_balance_of", "ft_transfer"])?;
+    Validator::new().validate_all(&wasm)?;
+    assert_eq!(
+        exports(&wasm)?,
+        ["memory", "mock_configure", "ft_balance_of", "ft_transfer"]
+    );
+
+    // The exports of the mock itself can't be mocked.
+    assert!(MockContract::wasm(&["mock_configure"]).is_err());
+    assert!(MockContract::wasm(&["memory"]).is_err());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_mock_contract() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let mock = worker.deploy_mock().await?;
+    let alice = worker.dev_create_account().await?;
+
+    mock.on("ft_balance_of").returns_json(&json!("100")).await?;
+    mock.on("ft_transfer").panics("not enough balance").await?;
+    mock.on("balance_later")
+        .returns_after_promise(mock.id(), Function::new("ft_balance_of"))
+        .await?;
+
+    let balance: String = alice
+        .call(mock.id(), "ft_balance_of")
+        .args_json(json!({ "account_id": alice.id() }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(balance, "100");
+
+    let result = alice.call(mock.id(), "ft_transfer").transact().await?;
+    assert!(result.is_failure());
+    assert!(format!("{:?}", result.into_result()).contains("not enough balance"));
+
+    let balance: String = alice
+        .call(mock.id(), "balance_later")
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(balance, "100");
+
+    // Reprogramming a method replaces its response without redeploying the mock, keeping the
+    // recorded calls.
+    let code_hash = mock.contract().view_account().await?.contract_state;
+    mock.on("ft_balance_of").returns_json(&json!("0")).await?;
+    assert_eq!(
+        mock.contract().view_account().await?.contract_state,
+        code_hash
+    );
+    let balance: String = alice
+        .call(mock.id(), "ft_balance_of")
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(balance, "0");
+
+    let calls = mock.calls().await?;
+    let methods = calls
+        .iter()
+        .map(|call| call.method.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        methods,
+        [
+            "ft_balance_of",
+            "balance_later",
+            "ft_balance_of",
+            "ft_balance_of"
+        ]
+    );
+    assert_eq!(calls[0].predecessor_id, *alice.id());
+    assert_eq!(calls[0].deposit, NearToken::from_yoctonear(1));
+    assert_eq!(
+        calls[0].args_json::<serde_json::Value>()?,
+        json!({ "account_id": alice.id() })
+    );
+    // The call made by the promise comes from the mock itself.
+    assert_eq!(calls[2].predecessor_id, *mock.id());
+    assert_eq!(mock.calls_to("balance_later").await?.len(), 1);
+
+    Ok(())
+}
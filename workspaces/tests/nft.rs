@@ -0,0 +1,84 @@
+#![cfg(feature = "bundled-contracts")]
+use near_workspaces::types::nft::{NftContractMetadata, NftEvent, TokenMetadata, nft_events};
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_non_fungible_token() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let metadata = NftContractMetadata::new("Mountains", "MNT");
+    let nft = worker.deploy_nft(metadata.clone()).await?;
+    let owner = nft.contract().as_account();
+    let alice = worker.dev_create_account().await?;
+    let market = worker.dev_create_account().await?;
+    assert_eq!(nft.nft_metadata().await?, metadata);
+
+    let token_metadata = TokenMetadata {
+        title: Some("Olympus Mons".into()),
+        copies: Some(1),
+        ..Default::default()
+    };
+    let result = nft
+        .nft_mint(owner, "0", alice.id(), token_metadata.clone())
+        .await?;
+    let events = nft_events(&result)?;
+    let [NftEvent::NftMint(mints)] = events.as_slice() else {
+        panic!("expected a single mint event, got {events:?}");
+    };
+    assert_eq!(mints[0].owner_id, *alice.id());
+    assert_eq!(mints[0].token_ids, ["0"]);
+
+    // Only the owner of the contract can mint.
+    assert!(
+        nft.nft_mint(&alice, "1", alice.id(), TokenMetadata::default())
+            .await?
+            .is_failure()
+    );
+
+    let token = nft.nft_token("0").await?.unwrap();
+    assert_eq!(token.owner_id, *alice.id());
+    assert_eq!(token.metadata, Some(token_metadata));
+    assert!(nft.nft_token("1").await?.is_none());
+    assert_eq!(nft.nft_total_supply().await?, 1);
+    assert_eq!(nft.nft_supply_for_owner(alice.id()).await?, 1);
+
+    nft.nft_approve(&alice, "0", market.id(), None)
+        .await?
+        .into_result()?;
+    assert!(nft.nft_is_approved("0", market.id(), None).await?);
+    nft.nft_revoke(&alice, "0", market.id())
+        .await?
+        .into_result()?;
+    assert!(!nft.nft_is_approved("0", market.id(), None).await?);
+
+    nft.nft_approve(&alice, "0", market.id(), None)
+        .await?
+        .into_result()?;
+    let token = nft.nft_token("0").await?.unwrap();
+    let approval_id = token.approved_account_ids.unwrap()[market.id()];
+    let result = nft
+        .nft_transfer(&market, owner.id(), "0", Some(approval_id), Some("sold"))
+        .await?;
+    assert!(result.is_success());
+    let events = nft_events(&result)?;
+    let [NftEvent::NftTransfer(transfers)] = events.as_slice() else {
+        panic!("expected a single transfer event, got {events:?}");
+    };
+    assert_eq!(transfers[0].authorized_id.as_ref(), Some(market.id()));
+    assert_eq!(transfers[0].old_owner_id, *alice.id());
+    assert_eq!(transfers[0].memo.as_deref(), Some("sold"));
+
+    let tokens = nft.nft_tokens_for_owner(owner.id(), None, Some(10)).await?;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(nft.nft_supply_for_owner(alice.id()).await?, 0);
+
+    // Transferring to an account without `nft_on_transfer` returns the token.
+    let receiver = worker.deploy_mock().await?;
+    let kept: bool = nft
+        .nft_transfer_call(owner, receiver.id(), "0", None, "")
+        .await?
+        .json()?;
+    assert!(!kept);
+    assert_eq!(nft.nft_token("0").await?.unwrap().owner_id, *owner.id());
+
+    Ok(())
+}
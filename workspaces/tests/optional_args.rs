@@ -9,7 +9,7 @@ async fn init(
     worker: &Worker<impl DevNetwork + TopLevelAccountCreator>,
 ) -> anyhow::Result<Contract> {
     let contract = worker
-        .dev_deploy_tla(include_bytes!("../../examples/res/fungible_token.wasm"))
+        .dev_deploy_tla(include_bytes!("../res/fungible_token.wasm"))
         .await?;
 
     contract
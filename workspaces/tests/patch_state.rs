@@ -1,12 +1,13 @@
 // Required since `test_log` adds more recursion than the standard recursion limit of 128
 #![recursion_limit = "256"]
 
+use futures::StreamExt;
 use near_primitives::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_token::NearToken;
 use serde_json::json;
 use test_log::test;
 
-use near_workspaces::types::{KeyType, SecretKey};
+use near_workspaces::types::{KeyType, SecretKey, collections};
 use near_workspaces::{AccessKey, AccountDetailsPatch, AccountId, Contract, DevNetwork, Worker};
 
 const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
@@ -120,6 +121,33 @@ async fn test_patch() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test(tokio::test)]
+async fn test_patch_state_borsh() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, mut status_msg) = view_status_state(&worker).await?;
+    status_msg.records.push(Record {
+        k: "alice.near".to_string(),
+        v: "hello borsh".to_string(),
+    });
+
+    worker
+        .patch(&contract_id)
+        .state_borsh(b"STATE", &status_msg)
+        .transact()
+        .await?;
+
+    let status: String = worker
+        .view(&contract_id, "get_status")
+        .args_json(json!({
+            "account_id": "alice.near",
+        }))
+        .await?
+        .json()?;
+    assert_eq!(status, "hello borsh".to_string());
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn test_patch_full() -> anyhow::Result<()> {
     let worker = near_workspaces::sandbox().await?;
@@ -210,3 +238,388 @@ async fn test_patch_account_from_current() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_view_state_pages() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, _) = view_status_state(&worker).await?;
+
+    let states = (0..25)
+        .map(|i| (format!("key{i:02}").into_bytes(), vec![i as u8; 16]))
+        .collect::<Vec<_>>();
+    worker
+        .patch(&contract_id)
+        .states(states.iter().map(|(k, v)| (k.as_slice(), v.as_slice())))
+        .transact()
+        .await?;
+
+    let pages = worker
+        .view_state_pages(&contract_id)
+        .prefix(b"key")
+        .page_size(4)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(pages.len(), 7);
+    assert!(pages.iter().all(|page| page.len() <= 4));
+    assert_eq!(pages.concat(), states);
+
+    let page = worker
+        .view_state(&contract_id)
+        .prefix(b"key")
+        .after_key(b"key05")
+        .limit(3)
+        .await?;
+    let mut keys = page.into_keys().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![b"key06".to_vec(), b"key07".to_vec(), b"key08".to_vec()]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_import_contract_paged_state() -> anyhow::Result<()> {
+    let from = near_workspaces::sandbox().await?;
+    let (contract_id, status_msg) = view_status_state(&from).await?;
+
+    let states = (0..25)
+        .map(|i| (format!("key{i:02}").into_bytes(), vec![i as u8; 16]))
+        .collect::<Vec<_>>();
+    from.patch(&contract_id)
+        .states(states.iter().map(|(k, v)| (k.as_slice(), v.as_slice())))
+        .transact()
+        .await?;
+
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker
+        .import_contract(&contract_id, &from)
+        .with_data()
+        .state_page_size(4)
+        .patch_batch_size(64)
+        .transact()
+        .await?;
+
+    let mut imported = contract.view_state().await?;
+    let state = imported
+        .remove(b"STATE".as_slice())
+        .ok_or_else(|| anyhow::anyhow!("Could not retrieve STATE"))?;
+    assert_eq!(StatusMessage::try_from_slice(&state)?, status_msg);
+    assert_eq!(imported, states.into_iter().collect());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_import_contracts_with_dependencies() -> anyhow::Result<()> {
+    let from = near_workspaces::sandbox().await?;
+    let wasm = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+    let root = from.root_account()?;
+    let mut contracts = Vec::new();
+    for name in ["exchange", "token", "oracle"] {
+        let contract = root
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await?
+            .into_result()?
+            .deploy(&wasm)
+            .await?
+            .into_result()?;
+        contracts.push(contract);
+    }
+    let [exchange, token, oracle] = contracts.try_into().unwrap();
+
+    // Store the token's account ID within the state of the exchange.
+    token
+        .as_account()
+        .call(exchange.id(), "set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let worker = near_workspaces::sandbox().await?;
+    let imported = worker
+        .import_contracts(&from, [exchange.id().clone()])
+        .dependencies([oracle.id().clone()])
+        .with_data()
+        .scan_state()
+        .transact()
+        .await?;
+    let imported_ids = imported.iter().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(imported_ids, vec![exchange.id(), oracle.id(), token.id()]);
+
+    let status: String = worker
+        .view(exchange.id(), "get_status")
+        .args_json(json!({ "account_id": token.id() }))
+        .await?
+        .json()?;
+    assert_eq!(status, "hello");
+
+    // Without scanning the state, only the roots and dependencies are imported.
+    let worker = near_workspaces::sandbox().await?;
+    let imported = worker
+        .import_contracts(&from, [exchange.id().clone()])
+        .transact()
+        .await?;
+    assert_eq!(imported.len(), 1);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_dump_and_load_records() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, status_msg) = view_status_state(&worker).await?;
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("records.json");
+    worker.dump_accounts([&contract_id], &path).await?;
+
+    // Load the dumped state into a fresh sandbox, without deploying anything to it.
+    let other = near_workspaces::sandbox().await?;
+    other.load_records(&path).await?;
+
+    let account = other.view_account(&contract_id).await?;
+    assert_eq!(
+        account.contract_state,
+        worker.view_account(&contract_id).await?.contract_state
+    );
+    assert_eq!(
+        other.view_code(&contract_id).await?,
+        std::fs::read(STATUS_MSG_WASM_FILEPATH)?
+    );
+    assert_eq!(
+        other.view_access_keys(&contract_id).await?.len(),
+        worker.view_access_keys(&contract_id).await?.len()
+    );
+
+    let state = other.view_state(&contract_id).await?;
+    assert_eq!(
+        StatusMessage::try_from_slice(&state[b"STATE".as_slice()])?,
+        status_msg
+    );
+    let status: String = other
+        .view(&contract_id, "get_status")
+        .args_json(json!({ "account_id": contract_id }))
+        .await?
+        .json()?;
+    assert_eq!(status, "hello");
+
+    Ok(())
+}
+
+#[cfg(feature = "bundled-contracts")]
+#[test(tokio::test)]
+async fn test_patch_deletions() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, _) = view_status_state(&worker).await?;
+    let before = worker.view_account(&contract_id).await?;
+
+    worker
+        .patch(&contract_id)
+        .states([
+            (b"a1".as_slice(), b"1".as_slice()),
+            (b"a2".as_slice(), b"2".as_slice()),
+            (b"b1".as_slice(), b"3".as_slice()),
+        ])
+        .transact()
+        .await?;
+    worker
+        .patch(&contract_id)
+        .delete_state(b"STATE")
+        .delete_state_prefix(b"a")
+        .transact()
+        .await?;
+
+    let state = worker.view_state(&contract_id).await?;
+    assert_eq!(state.into_keys().collect::<Vec<_>>(), vec![b"b1".to_vec()]);
+    // The code and balance of the account are untouched by deleting state.
+    let after = worker.view_account(&contract_id).await?;
+    assert_eq!(after.contract_state, before.contract_state);
+    assert_eq!(after.balance, before.balance);
+    assert_eq!(
+        worker.view_code(&contract_id).await?,
+        std::fs::read(STATUS_MSG_WASM_FILEPATH)?
+    );
+
+    // Revoke an access key.
+    let pk = SecretKey::from_random(KeyType::ED25519).public_key();
+    worker
+        .patch(&contract_id)
+        .access_key(pk.clone(), AccessKey::full_access())
+        .transact()
+        .await?;
+    worker
+        .patch(&contract_id)
+        .delete_access_key(pk.clone())
+        .transact()
+        .await?;
+    assert!(worker.view_access_key(&contract_id, &pk).await.is_err());
+
+    worker.patch(&contract_id).clear_code().transact().await?;
+    assert_eq!(
+        worker.view_account(&contract_id).await?.contract_state,
+        near_workspaces::ContractState::None
+    );
+
+    worker
+        .patch(&contract_id)
+        .delete_account()
+        .transact()
+        .await?;
+    assert!(worker.view_account(&contract_id).await.is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "bundled-contracts")]
+#[test(tokio::test)]
+async fn test_patch_deletions_failure() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, _) = view_status_state(&worker).await?;
+    let before = worker.view_account(&contract_id).await?;
+    let keys_before = worker.view_access_keys(&contract_id).await?;
+
+    // Removing a key the account does not have fails the transaction making the deletions.
+    let missing_pk = SecretKey::from_random(KeyType::ED25519).public_key();
+    let result = worker
+        .patch(&contract_id)
+        .delete_state(b"STATE")
+        .delete_access_key(missing_pk)
+        .transact()
+        .await;
+    assert!(result.is_err());
+
+    // The account is left with its code, balance and keys, rather than the state cleaner and
+    // the temporary key used to make the deletions.
+    let after = worker.view_account(&contract_id).await?;
+    assert_eq!(after.contract_state, before.contract_state);
+    assert_eq!(after.balance, before.balance);
+    assert_eq!(
+        worker.view_code(&contract_id).await?,
+        std::fs::read(STATUS_MSG_WASM_FILEPATH)?
+    );
+    assert_eq!(
+        worker.view_access_keys(&contract_id).await?.len(),
+        keys_before.len()
+    );
+    // The state removal was part of the failed transaction, so it was not made either.
+    assert!(
+        worker
+            .view_state(&contract_id)
+            .await?
+            .contains_key(b"STATE".as_slice())
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_view_state_typed_and_collections() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, status_msg) = view_status_state(&worker).await?;
+    let contract = Contract::from_secret_key(
+        contract_id.clone(),
+        SecretKey::from_random(KeyType::ED25519),
+        &worker,
+    );
+    assert_eq!(
+        contract.view_state_typed::<StatusMessage>().await?,
+        status_msg
+    );
+
+    // Lay out collections the same way near-sdk would, and read them back.
+    let alice: AccountId = "alice.near".parse()?;
+    let bob: AccountId = "bob.near".parse()?;
+    worker
+        .patch(&contract_id)
+        .state_borsh(&collections::lookup_map_key(b"b", &alice)?, &100u128)
+        .state_borsh(&collections::lookup_map_key(b"b", &bob)?, &200u128)
+        .state_borsh(&collections::vector_index_key(b"v", 1), "second")
+        .state_borsh(&collections::vector_index_key(b"v", 0), "first")
+        .state_borsh(&collections::lookup_map_key(b"im", &bob)?, &(2u8, 1u32))
+        .state_borsh(&collections::lookup_map_key(b"im", &alice)?, &(1u8, 0u32))
+        .transact()
+        .await?;
+
+    let balances = contract.read_lookup_map::<AccountId, u128>(b"b").await?;
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[&alice], 100);
+    assert_eq!(balances[&bob], 200);
+
+    let vector = contract.read_vector::<String>(b"v").await?;
+    assert_eq!(vector, vec!["first".to_string(), "second".to_string()]);
+
+    let map = contract.read_iterable_map::<AccountId, u8>(b"i").await?;
+    assert_eq!(map, vec![(alice, 1), (bob, 2)]);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_patch_many() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let (contract_id, _) = view_status_state(&worker).await?;
+    let wasm = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+
+    let holders = (0..50)
+        .map(|i| format!("holder-{i}.test.near").parse())
+        .collect::<Result<Vec<AccountId>, _>>()?;
+    let pk = SecretKey::from_random(KeyType::ED25519).public_key();
+    let mut patch =
+        worker
+            .patch_many()
+            .code(&contract_id, &wasm)
+            .state(&contract_id, b"key", b"value");
+    for holder in &holders {
+        patch = patch
+            .account(
+                holder,
+                AccountDetailsPatch::default().balance(NearToken::from_near(1)),
+            )
+            .access_key(holder, pk.clone(), AccessKey::full_access());
+    }
+    let summary = patch.transact().await?;
+    assert_eq!(summary.accounts.len(), holders.len() + 1);
+    assert_eq!(summary.records, 2 * holders.len() + 3);
+    assert_eq!(summary.requests, 1);
+
+    for holder in &holders {
+        let account = worker.view_account(holder).await?;
+        assert_eq!(account.balance, NearToken::from_near(1));
+        worker.view_access_key(holder, &pk).await?;
+    }
+    let state = worker.view_state(&contract_id).prefix(b"key").await?;
+    assert_eq!(state[b"key".as_slice()], b"value".to_vec());
+
+    // Records exceeding the payload limit of a single request are not patched, unless
+    // explicitly split into multiple requests.
+    let patch = || {
+        worker
+            .patch_many()
+            .states(
+                &contract_id,
+                [
+                    (b"a".as_slice(), [0; 100].as_slice()),
+                    (b"b".as_slice(), [1; 100].as_slice()),
+                ],
+            )
+            .max_payload_size(200)
+    };
+    assert!(patch().transact().await.is_err());
+    assert!(
+        worker
+            .view_state(&contract_id)
+            .prefix(b"a")
+            .await?
+            .is_empty()
+    );
+    let summary = patch().transact_in_batches().await?;
+    assert_eq!(summary.requests, 2);
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use serde_json::json;
+use test_log::test;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+const ADDER_WASM_FILEPATH: &str = "../examples/res/adder.wasm";
+
+#[test(tokio::test)]
+async fn test_replay_transactions() -> anyhow::Result<()> {
+    // Record some traffic on one sandbox, standing in for mainnet.
+    let origin = near_workspaces::sandbox().await?;
+    let contract = origin
+        .dev_deploy(&std::fs::read(STATUS_MSG_WASM_FILEPATH)?)
+        .await?;
+    let alice = origin.dev_create_account().await?;
+    let start = origin.view_block().await?.height() + 1;
+    for message in ["hello", "world"] {
+        alice
+            .call(contract.id(), "set_status")
+            .args_json(json!({ "message": message }))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    let end = origin.view_block().await?.height() + 1;
+
+    let worker = near_workspaces::sandbox().await?;
+    let report = worker
+        .replay_transactions(&origin, contract.id(), start..end)
+        .transact()
+        .await?;
+    assert_eq!(report.calls.len(), 2);
+    assert!(
+        report
+            .calls
+            .iter()
+            .all(|call| call.predecessor_id == *alice.id() && call.result.is_ok())
+    );
+    assert_eq!(report.divergent().count(), 0);
+
+    let status: String = report
+        .contract
+        .view("get_status")
+        .args_json(json!({ "account_id": alice.id() }))
+        .await?
+        .json()?;
+    assert_eq!(status, "world");
+
+    // Replaying against a contract without the called function diverges.
+    let worker = near_workspaces::sandbox().await?;
+    let report = worker
+        .replay_transactions(&origin, contract.id(), start..end)
+        .wasm(&std::fs::read(ADDER_WASM_FILEPATH)?)
+        .transact()
+        .await?;
+    assert_eq!(report.divergent().count(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "bundled-contracts")]
+use near_workspaces::types::NearToken;
+use near_workspaces::types::staking::RewardFeeFraction;
+use test_log::test;
+
+/// Blocks to fast forward by between checks of whether unstaked NEAR became withdrawable.
+const FAST_FORWARD_BLOCKS: u64 = 100;
+
+/// Upper bound of blocks to wait for unstaked NEAR to become withdrawable, which takes 4
+/// epochs.
+const MAX_UNLOCK_BLOCKS: u64 = 10_000;
+
+#[test(tokio::test)]
+async fn test_staking_pool() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let reward_fee = RewardFeeFraction::new(10, 100);
+    let pool = worker.deploy_staking_pool(owner.id(), reward_fee).await?;
+    assert_eq!(pool.get_owner_id().await?, *owner.id());
+    assert_eq!(pool.get_reward_fee_fraction().await?, reward_fee);
+    assert!(pool.stake_secret_key().is_some());
+
+    pool.deposit_and_stake(&alice, NearToken::from_near(5))
+        .await?
+        .into_result()?;
+    let account = pool.get_account(alice.id()).await?;
+    assert_eq!(account.unstaked_balance, NearToken::from_yoctonear(0));
+    // Shares are rounded down, which may leave a few yoctoNEAR unstaked.
+    assert!(account.staked_balance <= NearToken::from_near(5));
+    assert!(account.staked_balance > NearToken::from_millinear(4999));
+
+    pool.unstake(&alice, NearToken::from_near(2))
+        .await?
+        .into_result()?;
+    let account = pool.get_account(alice.id()).await?;
+    assert!(account.unstaked_balance >= NearToken::from_near(2));
+    assert!(!account.can_withdraw);
+
+    // Unstaked NEAR can't be withdrawn before the unstaking delay.
+    assert!(
+        pool.withdraw(&alice, NearToken::from_near(2))
+            .await?
+            .is_failure()
+    );
+
+    let mut blocks = 0;
+    while !pool.get_account(alice.id()).await?.can_withdraw {
+        assert!(
+            blocks < MAX_UNLOCK_BLOCKS,
+            "unstaked NEAR still locked after {blocks} blocks"
+        );
+        worker.fast_forward(FAST_FORWARD_BLOCKS).await?;
+        blocks += FAST_FORWARD_BLOCKS;
+    }
+
+    let before = alice.view_account().await?.balance;
+    pool.withdraw(&alice, NearToken::from_near(2))
+        .await?
+        .into_result()?;
+    let after = alice.view_account().await?.balance;
+    assert!(after > before.saturating_add(NearToken::from_millinear(1900)));
+    let account = pool.get_account(alice.id()).await?;
+    assert!(account.unstaked_balance < NearToken::from_near(1));
+    assert!(account.staked_balance > NearToken::from_millinear(2999));
+
+    Ok(())
+}
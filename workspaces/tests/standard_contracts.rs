@@ -0,0 +1,160 @@
+#![cfg(feature = "bundled-contracts")]
+use near_workspaces::AccountId;
+use near_workspaces::types::multisig::MultisigMember;
+use near_workspaces::types::{KeyType, NearToken, SecretKey};
+use serde_json::json;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_standard_contracts() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().with_standard_contracts().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let wrap_near = worker.wrap_near()?;
+    assert_eq!(wrap_near.id().as_str(), "wrap.near");
+    assert_eq!(wrap_near.ft_metadata().await?.symbol, "wNEAR");
+
+    // The first deposit registers the account, paying for its storage out of the deposit.
+    let bounds = wrap_near.storage_balance_bounds().await?;
+    alice
+        .call(wrap_near.id(), "near_deposit")
+        .deposit(NearToken::from_near(1).saturating_add(bounds.min))
+        .transact()
+        .await?
+        .into_result()?;
+    let one_near = NearToken::from_near(1).as_yoctonear();
+    assert_eq!(wrap_near.ft_balance_of(alice.id()).await?, one_near);
+    assert_eq!(wrap_near.ft_total_supply().await?, one_near);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_create_near_account() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().with_standard_contracts().await?;
+    let linkdrop = worker.linkdrop()?;
+    assert_eq!(linkdrop.id().as_str(), "near");
+
+    let id: AccountId = "alice.near".parse()?;
+    let sk = SecretKey::from_random(KeyType::ED25519);
+    let alice = worker
+        .create_near_account(id.clone(), sk.clone(), NearToken::from_near(10))
+        .await?
+        .into_result()?;
+    assert_eq!(alice.id(), &id);
+    assert_eq!(
+        alice.view_account().await?.balance,
+        NearToken::from_near(10)
+    );
+
+    // Creating it again refunds the deposit instead of failing the transaction.
+    let root = worker.root_account()?;
+    let created: bool = linkdrop
+        .create_account(&root, &id, &sk.public_key(), NearToken::from_near(10))
+        .await?
+        .json()?;
+    assert!(!created);
+    assert!(
+        worker
+            .create_near_account(id, sk, NearToken::from_near(10))
+            .await
+            .is_err()
+    );
+
+    // NEAR dropped to a key is claimed into a new account.
+    let drop_sk = SecretKey::from_random(KeyType::ED25519);
+    linkdrop
+        .send(&root, &drop_sk.public_key(), NearToken::from_near(5))
+        .await?
+        .into_result()?;
+    assert_eq!(
+        linkdrop.get_key_balance(&drop_sk.public_key()).await?,
+        NearToken::from_near(4)
+    );
+    let bob: AccountId = "bob.near".parse()?;
+    let bob_sk = SecretKey::from_random(KeyType::ED25519);
+    let created: bool = linkdrop
+        .create_account_and_claim(&drop_sk, &bob, &bob_sk.public_key())
+        .await?
+        .json()?;
+    assert!(created);
+    let bob = worker.view_account(&bob).await?;
+    assert_eq!(bob.balance, NearToken::from_near(4));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_multisig_factory() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().with_standard_contracts().await?;
+    let factory = worker.multisig_factory()?;
+    assert_eq!(factory.id().as_str(), "multisig.near");
+
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    let members = [
+        MultisigMember::Account {
+            account_id: alice.id().clone(),
+        },
+        MultisigMember::Account {
+            account_id: bob.id().clone(),
+        },
+    ];
+    let created: bool = factory
+        .create(
+            &worker.root_account()?,
+            "team",
+            &members,
+            2,
+            NearToken::from_near(10),
+        )
+        .await?
+        .json()?;
+    assert!(created);
+
+    let multisig: AccountId = "team.multisig.near".parse()?;
+    let listed: Vec<MultisigMember> = worker.view(&multisig, "get_members").await?.json()?;
+    assert_eq!(listed.len(), 2);
+    assert!(members.iter().all(|member| listed.contains(member)));
+
+    // A transfer runs once both members confirmed it.
+    let request_id: u32 = alice
+        .call(&multisig, "add_request")
+        .args_json(json!({
+            "request": {
+                "receiver_id": bob.id(),
+                "actions": [{ "type": "Transfer", "amount": NearToken::from_near(1) }],
+            },
+        }))
+        .transact()
+        .await?
+        .json()?;
+    let confirmed: bool = alice
+        .call(&multisig, "confirm")
+        .args_json(json!({ "request_id": request_id }))
+        .transact()
+        .await?
+        .json()?;
+    assert!(confirmed);
+    let before = bob.view_account().await?.balance;
+    bob.call(&multisig, "confirm")
+        .args_json(json!({ "request_id": request_id }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    let after = bob.view_account().await?.balance;
+    assert!(after > before);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_no_standard_contracts_by_default() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    assert!(worker.wrap_near().is_err());
+    assert!(worker.linkdrop().is_err());
+    assert!(worker.multisig_factory().is_err());
+
+    Ok(())
+}
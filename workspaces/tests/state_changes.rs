@@ -0,0 +1,166 @@
+use futures::StreamExt;
+use serde_json::json;
+use test_log::test;
+
+use near_workspaces::types::StateChange;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[test(tokio::test)]
+async fn test_state_changes_in_block() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker
+        .dev_deploy(&std::fs::read(STATUS_MSG_WASM_FILEPATH)?)
+        .await?;
+
+    let outcome = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await?
+        .into_result()?;
+    let block_hash = outcome.receipt_outcomes()[0].block_hash;
+
+    let changes = worker
+        .state_changes()
+        .block_hash(block_hash)
+        .account(contract.id())
+        .await?;
+    assert_eq!(changes.block_hash, block_hash);
+    assert!(
+        changes
+            .changes
+            .iter()
+            .all(|c| c.account_id() == contract.id())
+    );
+    assert!(
+        changes
+            .changes
+            .iter()
+            .any(|c| matches!(c, StateChange::AccountUpdate { .. }))
+    );
+    assert_eq!(changes.data_keys().collect::<Vec<_>>(), vec![b"STATE"]);
+
+    // Only the contract storage changes are returned when filtering by key prefix.
+    let changes = worker
+        .state_changes()
+        .block_hash(block_hash)
+        .account(contract.id())
+        .key_prefix(b"STATE")
+        .await?;
+    assert!(!changes.is_empty());
+    assert!(
+        changes
+            .changes
+            .iter()
+            .all(|c| matches!(c, StateChange::DataUpdate { key, .. } if key == b"STATE"))
+    );
+
+    let changes = worker
+        .state_changes()
+        .block_hash(block_hash)
+        .account(contract.id())
+        .key_prefix(b"unknown")
+        .await?;
+    assert!(changes.is_empty());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_state_changes_range() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let start = worker.view_block().await?.height();
+    let contract = worker
+        .dev_deploy(&std::fs::read(STATUS_MSG_WASM_FILEPATH)?)
+        .await?;
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await?
+        .into_result()?;
+    let end = worker.view_block().await?.height() + 1;
+
+    let changes = worker
+        .state_changes_range(start..end)
+        .account(contract.id())
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for pair in changes.windows(2) {
+        assert!(pair[1].block_height > pair[0].block_height);
+    }
+    let all = changes.iter().flat_map(|c| &c.changes).collect::<Vec<_>>();
+    assert!(all.iter().all(|c| c.account_id() == contract.id()));
+    assert!(
+        all.iter()
+            .any(|c| matches!(c, StateChange::ContractCodeUpdate { .. }))
+    );
+    assert!(
+        all.iter()
+            .any(|c| matches!(c, StateChange::DataUpdate { key, .. } if key == b"STATE"))
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_contract_state_diff() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker
+        .dev_deploy(&std::fs::read(STATUS_MSG_WASM_FILEPATH)?)
+        .await?;
+    let before = worker.view_block().await?.height();
+
+    let outcome = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await?
+        .into_result()?;
+    let first = worker
+        .view_block()
+        .block_hash(outcome.receipt_outcomes()[0].block_hash)
+        .await?
+        .height();
+
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "world" }))
+        .transact()
+        .await?
+        .into_result()?;
+    let latest = worker.view_block().await?.height();
+
+    let diff = contract.state_diff(before, first).await?;
+    assert!(diff.removed.is_empty() && diff.modified.is_empty());
+    assert_eq!(diff.added.len(), 1);
+    let state = contract.view_state().block_height(first).await?;
+    assert_eq!(
+        diff.added.get(b"STATE".as_slice()),
+        state.get(b"STATE".as_slice())
+    );
+
+    let diff = contract.state_diff(first, latest).await?;
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    let change = &diff.modified[b"STATE".as_slice()];
+    assert_eq!(Some(&change.old), state.get(b"STATE".as_slice()));
+    assert_ne!(change.old, change.new);
+
+    // Walking the changes of every block in between gives the same diff.
+    assert_eq!(
+        contract.state_diff(first, latest).walk_blocks().await?,
+        diff
+    );
+
+    let diff = contract
+        .state_diff(before, latest)
+        .prefix(b"unknown")
+        .await?;
+    assert!(diff.is_empty());
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use near_primitives::borsh::{self, BorshDeserialize};
 
 use near_workspaces::AccountId;
-use near_workspaces::types::{KeyType, PublicKey, SecretKey};
+use near_workspaces::types::{DEFAULT_HD_PATH, KeyType, PublicKey, PublicKeyHandle, SecretKey};
 
 fn default_workspaces_pubkey() -> anyhow::Result<PublicKey> {
     let data = bs58::decode("279Zpep9MBBg4nKsVmTQE7NbXZkWdxti6HS1yzhp8qnc1ExS7gU").into_vec()?;
@@ -22,6 +22,10 @@ fn test_keypair_ed25519() -> anyhow::Result<()> {
     assert_eq!(pk, serde_json::from_str(pk_expected)?);
     assert_eq!(sk, serde_json::from_str(sk_expected)?);
 
+    // The secret key is only available through `expose_secret`.
+    assert_eq!(format!("\"{}\"", sk.expose_secret()), sk_expected);
+    assert_eq!(format!("{sk:?}"), "SecretKey(ed25519:<redacted>)");
+
     Ok(())
 }
 
@@ -62,7 +66,7 @@ fn test_keypair_mldsa65() -> anyhow::Result<()> {
         assert!(pk_str.starts_with("ml-dsa-65:"), "got {pk_str}");
         assert_eq!(PublicKey::from_str(&pk_str)?, pk);
 
-        let sk_str = sk.to_string();
+        let sk_str = sk.expose_secret();
         assert!(sk_str.starts_with("ml-dsa-65:"), "got {sk_str}");
         assert_eq!(SecretKey::from_str(&sk_str)?, sk);
     }
@@ -74,6 +78,29 @@ fn test_keypair_mldsa65() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_pubkey_handle() -> anyhow::Result<()> {
+    let pk = SecretKey::from_seed(KeyType::ED25519, "test").public_key();
+    let handle = PublicKeyHandle::from(&pk);
+    assert!(matches!(handle.key_type(), KeyType::ED25519));
+    assert_eq!(handle.full_public_key(), Some(pk.clone()));
+    assert_eq!(handle.to_string(), pk.to_string());
+
+    // Only the hash of ML-DSA-65 keys is kept, so the full key cannot be recovered.
+    let pq_pk = SecretKey::from_seed(KeyType::MLDSA65, "test").public_key();
+    let pq_handle = PublicKeyHandle::from(&pq_pk);
+    assert!(matches!(pq_handle.key_type(), KeyType::MLDSA65));
+    assert_eq!(pq_handle.full_public_key(), None);
+    assert!(pq_handle.matches(&pq_pk));
+    assert!(!pq_handle.matches(&pk));
+    assert_eq!(
+        PublicKeyHandle::from_str(&pq_handle.to_string())?,
+        pq_handle
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_pubkey_serialization() -> anyhow::Result<()> {
     for key_type in [KeyType::ED25519, KeyType::SECP256K1] {
@@ -140,3 +167,94 @@ fn test_valid_account_id() {
         "Something changed underneath for testnet to not be a valid Account ID"
     );
 }
+
+#[test]
+fn test_implicit_account_ids() -> anyhow::Result<()> {
+    let pk = SecretKey::from_seed(KeyType::ED25519, "test").public_key();
+    let id = pk.implicit_account_id()?;
+    let hex: String = pk.key_data().iter().map(|b| format!("{b:02x}")).collect();
+    assert_eq!(id.as_str(), hex);
+    assert!(pk.eth_implicit_account_id().is_err());
+
+    let pk = SecretKey::from_seed(KeyType::SECP256K1, "test").public_key();
+    let id = pk.eth_implicit_account_id()?;
+    assert_eq!(id.as_str(), "0x96791e923f8cf697ad9c3290f2c9059f0231b24c");
+    assert!(pk.implicit_account_id().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_seed_phrase() -> anyhow::Result<()> {
+    let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    let sk = SecretKey::from_seed_phrase(phrase, DEFAULT_HD_PATH)?;
+    assert_eq!(
+        sk.expose_secret(),
+        "ed25519:F1kPR175szkGxEL52A9H6Z5ocS2BtaipprK2Hiob9DjGzHTkScrBb1yt44baXPZ3LxyHcsTPdBjHmn6zx147txH"
+    );
+    assert_ne!(sk, SecretKey::from_seed_phrase(phrase, "m/44'/397'/1'")?);
+
+    // ED25519 derivation only supports hardened indices.
+    assert!(SecretKey::from_seed_phrase(phrase, "m/44'/397'/0").is_err());
+    assert!(SecretKey::from_seed_phrase("abandon about", DEFAULT_HD_PATH).is_err());
+
+    let (phrase, sk) = SecretKey::generate_seed_phrase();
+    assert_eq!(phrase.split_whitespace().count(), 12);
+    assert_eq!(sk, SecretKey::from_seed_phrase(&phrase, DEFAULT_HD_PATH)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_sdk_collection_keys() -> anyhow::Result<()> {
+    use near_sdk::store::{LookupMap, Vector};
+    use near_sdk::{env, test_utils::VMContextBuilder, testing_env};
+    use near_workspaces::types::collections as keys;
+
+    testing_env!(VMContextBuilder::new().build());
+    let alice: near_sdk::AccountId = "alice.near".parse()?;
+
+    let mut balances = LookupMap::<near_sdk::AccountId, u128>::new(b"b");
+    balances.insert(alice.clone(), 100);
+    balances.flush();
+    let value = env::storage_read(&keys::lookup_map_key(b"b", &alice)?).unwrap();
+    assert_eq!(u128::try_from_slice(&value)?, 100);
+
+    let mut hashed = LookupMap::<String, u8, near_sdk::store::key::Sha256>::with_hasher(b"h");
+    hashed.insert("key".to_string(), 7);
+    hashed.flush();
+    let value = env::storage_read(&keys::lookup_map_key_sha256(b"h", "key")?).unwrap();
+    assert_eq!(value, borsh::to_vec(&7u8)?);
+
+    let mut vector = Vector::<String>::new(b"v");
+    vector.extend(["first".to_string(), "second".to_string()]);
+    vector.flush();
+    let value = env::storage_read(&keys::vector_index_key(b"v", 1)).unwrap();
+    assert_eq!(String::try_from_slice(&value)?, "second");
+
+    Ok(())
+}
+
+#[test]
+fn test_staking_pool_types() -> anyhow::Result<()> {
+    use near_workspaces::types::NearToken;
+    use near_workspaces::types::staking::{RewardFeeFraction, StakingPoolAccount};
+
+    let account: StakingPoolAccount = serde_json::from_value(serde_json::json!({
+        "account_id": "alice.near",
+        "unstaked_balance": "1000000000000000000000000",
+        "staked_balance": "5",
+        "can_withdraw": true,
+    }))?;
+    assert_eq!(account.account_id, AccountId::from_str("alice.near")?);
+    assert_eq!(account.unstaked_balance, NearToken::from_near(1));
+    assert_eq!(account.staked_balance, NearToken::from_yoctonear(5));
+    assert!(account.can_withdraw);
+
+    assert_eq!(
+        serde_json::to_value(RewardFeeFraction::new(10, 100))?,
+        serde_json::json!({ "numerator": 10, "denominator": 100 })
+    );
+
+    Ok(())
+}
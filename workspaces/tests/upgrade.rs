@@ -0,0 +1,53 @@
+use near_primitives::borsh::BorshDeserialize;
+use serde_json::json;
+use test_log::test;
+
+use near_workspaces::operations::Function;
+use near_workspaces::testing::UpgradeTest;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[derive(BorshDeserialize)]
+#[borsh(crate = "near_primitives::borsh")]
+struct StatusMessage {
+    #[allow(dead_code)]
+    records: Vec<(String, String)>,
+}
+
+#[test(tokio::test)]
+async fn test_upgrade_harness() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let wasm = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+
+    // Upgrading to the same contract, with setting a status standing in for the migration.
+    let report = UpgradeTest::new(&worker, &wasm, &wasm)
+        .populate(|contract| async move {
+            contract
+                .call("set_status")
+                .args_json(json!({ "message": "hello" }))
+                .transact()
+                .await?
+                .into_result()?;
+            Ok(())
+        })
+        .migrate(Function::new("set_status").args_json(json!({ "message": "migrated" })))
+        .decoder(|key, value| key == b"STATE" && StatusMessage::try_from_slice(value).is_ok())
+        .view(Function::new("get_status").args_json(json!({ "account_id": "unknown.near" })))
+        .run()
+        .await?;
+
+    assert!(report.is_success(), "{report:?}");
+    assert!(report.removed_keys.is_empty());
+    assert!(report.migration_gas.as_gas() > 0);
+
+    // A failing migration reverts the deployment, and is reported rather than returned.
+    let report = UpgradeTest::new(&worker, &wasm, &wasm)
+        .migrate(Function::new("unknown_method"))
+        .decoder(|_, _| false)
+        .run()
+        .await?;
+    assert!(!report.is_success());
+    assert!(report.migration.is_failure());
+
+    Ok(())
+}